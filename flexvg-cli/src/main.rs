@@ -1,5 +1,5 @@
 use clap::Parser;
-use flexvg::compute_svg_string;
+use flexvg::{compute_layout, diagnostics::diagnose, svg::compute_svg_string};
 use log::debug;
 use std::fs::File;
 use std::io::BufReader;
@@ -12,6 +12,9 @@ struct Cli {
     input: std::path::PathBuf,
     #[clap(parse(from_os_str))]
     output: Option<std::path::PathBuf>,
+    /// Draw the box, edges and size of every node over the diagram
+    #[clap(long)]
+    debug: bool,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -24,7 +27,7 @@ fn main() -> anyhow::Result<()> {
         .ok_or_else(|| anyhow::format_err!("No base path"))?;
     let input = File::open(&args.input)?;
     let buf_reader = BufReader::new(input);
-    let root = match args
+    let mut root: flexvg::types::FlexNode = match args
         .input
         .extension()
         .ok_or_else(|| anyhow::format_err!("expected extension"))?
@@ -37,8 +40,15 @@ fn main() -> anyhow::Result<()> {
             "unsupported extension, input file must be yaml or json"
         ),
     };
+    if args.debug {
+        root.root.get_or_insert_with(Default::default).debug = true;
+    }
     debug!("{:?}", root);
-    let svg = compute_svg_string(root, Some(base))?;
+    let layout = compute_layout(root, Some(base))?;
+    for diagnostic in diagnose(&layout) {
+        eprintln!("warning: {diagnostic}");
+    }
+    let svg = compute_svg_string(layout, Some(base))?;
     let output = args.output.unwrap_or_else(|| {
         let mut path = args.input.clone();
         path.set_extension("svg");
@@ -0,0 +1,225 @@
+use crate::prelude::*;
+use std::str::FromStr;
+
+/// Parses a `calc()` expression, e.g. `calc(50% - 2 * 10pt)`, into a linear
+/// combination of units. Lengths may be added and subtracted, and
+/// multiplied or divided by numbers.
+pub(crate) fn parse_calc(s: &str) -> Result<FlexCalc, String> {
+    let mut parser = Parser { tokens: tokenize(s)?, index: 0 };
+    let value = parser.expression()?;
+    if parser.index != parser.tokens.len() {
+        return Err(format!("unexpected token in `{s}`"));
+    }
+    Ok(value.calc)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Value(FlexDimension),
+    Number(f32),
+    Operator(char),
+    Open,
+    Close,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut rest = s.trim();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("calc(") {
+            tokens.push(Token::Open);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('(') {
+            tokens.push(Token::Open);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix(')') {
+            tokens.push(Token::Close);
+            rest = after;
+        } else if rest.starts_with(['*', '/', '+']) || is_minus(rest) {
+            tokens.push(Token::Operator(rest.chars().next().unwrap()));
+            rest = &rest[1..];
+        } else {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || "()*/".contains(c))
+                .unwrap_or(rest.len());
+            let (word, after) = rest.split_at(end);
+            tokens.push(match f32::from_str(word) {
+                Ok(number) => Token::Number(number),
+                Err(_) => Token::Value(FlexDimension::from_str(word)?),
+            });
+            rest = after;
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+/// A `-` that isn't the sign of a number
+fn is_minus(s: &str) -> bool {
+    s.strip_prefix('-').is_some_and(|rest| {
+        rest.starts_with(|c: char| c.is_whitespace() || c == '(')
+    })
+}
+
+/// A linear combination of units, or a plain number
+struct Value {
+    calc: FlexCalc,
+    number: Option<f32>,
+}
+
+impl Value {
+    fn scale(self, factor: f32) -> Value {
+        Value {
+            calc: self.calc * factor,
+            number: self.number.map(|number| number * factor),
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn peek_operator(&self, operators: &str) -> Option<char> {
+        match self.tokens.get(self.index) {
+            Some(Token::Operator(c)) if operators.contains(*c) => Some(*c),
+            _ => None,
+        }
+    }
+
+    fn expression(&mut self) -> Result<Value, String> {
+        let mut value = self.term()?;
+        while let Some(operator) = self.peek_operator("+-") {
+            self.index += 1;
+            let mut rhs = self.term()?;
+            if operator == '-' {
+                rhs = rhs.scale(-1.0);
+            }
+            value = Value {
+                calc: value.calc + rhs.calc,
+                number: value.number.zip(rhs.number).map(|(a, b)| a + b),
+            };
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<Value, String> {
+        let mut value = self.factor()?;
+        while let Some(operator) = self.peek_operator("*/") {
+            self.index += 1;
+            let rhs = self.factor()?;
+            value = match (operator, value.number, rhs.number) {
+                ('*', _, Some(factor)) => value.scale(factor),
+                ('*', Some(factor), None) => rhs.scale(factor),
+                ('/', _, Some(divisor)) if divisor != 0.0 => {
+                    value.scale(1.0 / divisor)
+                }
+                ('/', _, Some(_)) => return Err("division by zero".into()),
+                _ => {
+                    return Err(format!(
+                        "`{operator}` needs a number on one side"
+                    ))
+                }
+            };
+        }
+        Ok(value)
+    }
+
+    fn factor(&mut self) -> Result<Value, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Value {
+                // @NOTE(jshrake): Bare numbers are lengths in user units
+                calc: FlexCalc { points: number, ..Default::default() },
+                number: Some(number),
+            }),
+            Some(Token::Value(dimension)) => {
+                Ok(Value { calc: FlexCalc::try_from(dimension)?, number: None })
+            }
+            Some(Token::Operator('-')) => Ok(self.factor()?.scale(-1.0)),
+            Some(Token::Open) => {
+                let value = self.expression()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(value),
+                    _ => Err("missing `)`".into()),
+                }
+            }
+            _ => Err("expected a length".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taffy::prelude::Dimension;
+
+    fn calc(s: &str) -> FlexCalc {
+        parse_calc(s).unwrap()
+    }
+
+    #[test]
+    fn parses_sums_of_units() {
+        let expected =
+            FlexCalc { percent: 0.5, points: -10.0, ..Default::default() };
+        assert_eq!(calc("calc(50% - 10pt)"), expected);
+        let expected =
+            FlexCalc { em: 1.0, vw: 5.0, vh: -2.0, ..Default::default() };
+        assert_eq!(calc("calc(1em + 5vw - 2vh)"), expected);
+    }
+
+    #[test]
+    fn parses_products_and_parentheses() {
+        let expected = FlexCalc { em: 2.0, points: 8.0, ..Default::default() };
+        assert_eq!(calc("calc(2 * (1em + 4pt))"), expected);
+        assert_eq!(calc("calc((1em + 4pt) * 2)"), expected);
+        let expected = FlexCalc { percent: 0.25, ..Default::default() };
+        assert_eq!(calc("calc(100% / 4)"), expected);
+        let expected =
+            FlexCalc { percent: 1.0, points: 20.0, ..Default::default() };
+        assert_eq!(calc("calc(100% - -20pt)"), expected);
+        assert_eq!(calc("calc(100% - (-20pt))"), expected);
+    }
+
+    #[test]
+    fn evaluates_against_the_basis() {
+        let units = FlexUnits::default();
+        let value = calc("calc(50% - 10pt)");
+        assert_eq!(value.resolve(&units, Some(200.0)), Dimension::Points(90.0));
+        // @NOTE(jshrake): Percentages stand in until the basis is known
+        assert_eq!(value.resolve(&units, None), Dimension::Percent(0.5));
+        let value = calc("calc(2em + 10vw)");
+        assert_eq!(value.resolve(&units, None), Dimension::Points(42.0));
+    }
+
+    #[test]
+    fn simplifies_single_units() {
+        let length = |s: &str| s.parse::<FlexDimension>().unwrap();
+        assert_eq!(length("calc(10pt + 5pt)"), FlexDimension::Points(15.0));
+        assert_eq!(length("calc(50% / 2)"), FlexDimension::Percent(0.25));
+        assert!(matches!(length("calc(50% - 1em)"), FlexDimension::Calc(_)));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        for s in [
+            "calc(10pt * 5pt)",
+            "calc(10pt / 0)",
+            "calc(10pt +)",
+            "calc(10pt",
+            "calc(10pt))",
+            "calc(10qq)",
+            "calc(auto)",
+            "calc()",
+        ] {
+            assert!(parse_calc(s).is_err(), "{s}");
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use crate::prelude::*;
+use crate::text::text_property;
+
+// @NOTE(jshrake): Taffy rounds layouts to whole units, so a box can poke
+// out of its parent by a rounding error
+const EPSILON: f32 = 0.5;
+
+/// A likely mistake in a laid out document.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub struct FlexDiagnostic {
+    /// Index of the node, and of each of its ancestors, in its parent's
+    /// `nodes`
+    pub path: Vec<usize>,
+    /// Whether the diagnostic is about the text or rect of the node's
+    /// `label`, rather than the node itself
+    pub label: bool,
+    /// Element of the node, if it has one
+    pub element: Option<String>,
+    pub kind: FlexDiagnosticKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub enum FlexDiagnosticKind {
+    /// The node extends past its parent's box by `overflow` on each edge
+    Overflow { overflow: FlexRect<f32> },
+    /// The root node extends past the canvas by `overflow` on each edge
+    CanvasOverflow { overflow: FlexRect<f32> },
+    /// An element laid out with no area
+    EmptyBox { width: f32, height: f32 },
+    /// A line of text is wider than its box
+    TextOverflow { text_width: f32, box_width: f32 },
+}
+
+/// Walks a laid out document and reports overflowing boxes, elements with
+/// zero or negative size and text that doesn't fit its box.
+pub fn diagnose(root: &FlexGeomNode) -> Vec<FlexDiagnostic> {
+    let mut diagnostics = vec![];
+    let root_data = root.root.clone().unwrap_or_default();
+    if root_data.canvas_sizing == FlexCanvasSizing::Fixed {
+        let canvas = FlexGeomNode {
+            width: root_data.canvas.width,
+            height: root_data.canvas.height,
+            ..Default::default()
+        };
+        if let Some(overflow) = overflow(&canvas, root) {
+            diagnostics.push(FlexDiagnostic {
+                path: vec![],
+                label: false,
+                element: element_name(root),
+                kind: FlexDiagnosticKind::CanvasOverflow { overflow },
+            });
+        }
+    }
+    diagnose_recursive(root, &mut vec![], &mut diagnostics);
+    diagnostics
+}
+
+fn diagnose_recursive(
+    node: &FlexGeomNode,
+    path: &mut Vec<usize>,
+    diagnostics: &mut Vec<FlexDiagnostic>,
+) {
+    let mut report = |kind| {
+        diagnostics.push(FlexDiagnostic {
+            path: path.clone(),
+            label: node.is_label,
+            element: element_name(node),
+            kind,
+        })
+    };
+    // @NOTE(jshrake): Nodes without an element only group their children,
+    // so an empty one isn't a problem by itself. A line only needs a length.
+    let is_empty = match element_name(node).as_deref() {
+        None => false,
+        Some("line") => node.width <= 0.0 && node.height <= 0.0,
+        Some(_) => node.width <= 0.0 || node.height <= 0.0,
+    };
+    if is_empty {
+        report(FlexDiagnosticKind::EmptyBox {
+            width: node.width,
+            height: node.height,
+        });
+    }
+    if let Some(text) = &node.text {
+        // @NOTE(jshrake): Rotated and scaled text no longer lines up with
+        // its box, so its width isn't checked either
+        let is_transformed = !node.transform.is_identity();
+        let is_clipped = node.overflow == FlexOverflow::Hidden;
+        if text.width > node.width + EPSILON
+            && !is_vertical(node)
+            && !is_transformed
+            && !is_clipped
+        {
+            report(FlexDiagnosticKind::TextOverflow {
+                text_width: text.width,
+                box_width: node.width,
+            });
+        }
+    }
+    for (i, child) in node.nodes.iter().enumerate() {
+        // @NOTE(jshrake): The text and rect of a label aren't in the
+        // document, so they're reported as the label of their parent
+        if !child.is_label {
+            path.push(i);
+        }
+        // @NOTE(jshrake): Overflow of a clipped node is hidden on purpose
+        let overflow = match node.overflow {
+            FlexOverflow::Visible => overflow(node, child),
+            FlexOverflow::Hidden => None,
+        };
+        if let Some(overflow) = overflow {
+            diagnostics.push(FlexDiagnostic {
+                path: path.clone(),
+                label: child.is_label,
+                element: element_name(child),
+                kind: FlexDiagnosticKind::Overflow { overflow },
+            });
+        }
+        diagnose_recursive(child, path, diagnostics);
+        if !child.is_label {
+            path.pop();
+        }
+    }
+}
+
+/// How far `child` extends past each edge of `parent`, if at all
+fn overflow(
+    parent: &FlexGeomNode,
+    child: &FlexGeomNode,
+) -> Option<FlexRect<f32>> {
+    let outer = parent.absolute_position;
+    let inner = child.absolute_position;
+    let overflow = FlexRect {
+        start: outer.x - inner.x,
+        end: (inner.x + child.width) - (outer.x + parent.width),
+        top: outer.y - inner.y,
+        bottom: (inner.y + child.height) - (outer.y + parent.height),
+    };
+    let edges = [overflow.start, overflow.end, overflow.top, overflow.bottom];
+    edges.iter().any(|edge| *edge > EPSILON).then_some(overflow)
+}
+
+/// Vertical text is measured as if it were horizontal, so its width can't
+/// be checked
+fn is_vertical(node: &FlexGeomNode) -> bool {
+    let writing_mode = node
+        .element
+        .as_ref()
+        .and_then(|element| text_property(element, "writing-mode"));
+    writing_mode.is_some_and(|mode| {
+        mode.starts_with("tb") || mode.starts_with("vertical")
+    })
+}
+
+fn element_name(node: &FlexGeomNode) -> Option<String> {
+    node.element.as_ref().map(|element| element.element.clone())
+}
+
+impl std::fmt::Display for FlexDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", NodePath(&self.path))?;
+        if self.label {
+            write!(f, ".label")?;
+        }
+        if let Some(element) = &self.element {
+            write!(f, " <{element}>")?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+impl std::fmt::Display for FlexDiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FlexDiagnosticKind::Overflow { overflow } => {
+                write!(f, "overflows its parent by {}", Edges(overflow))
+            }
+            FlexDiagnosticKind::CanvasOverflow { overflow } => {
+                write!(f, "overflows the canvas by {}", Edges(overflow))
+            }
+            FlexDiagnosticKind::EmptyBox { width, height } => {
+                write!(f, "has an empty {width}x{height} box")
+            }
+            FlexDiagnosticKind::TextOverflow { text_width, box_width } => {
+                write!(
+                    f,
+                    "text is {text_width} wide but its box is {box_width} wide"
+                )
+            }
+        }
+    }
+}
+
+/// Formats an index path like `nodes[1].nodes[0]`
+pub(crate) struct NodePath<'a>(pub &'a [usize]);
+
+impl std::fmt::Display for NodePath<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.0.is_empty() {
+            write!(f, "root")?;
+        }
+        for (depth, i) in self.0.iter().enumerate() {
+            let separator = if depth == 0 { "" } else { "." };
+            write!(f, "{separator}nodes[{i}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Lists the overflowing edges, e.g. `12 right, 3 bottom`
+struct Edges<'a>(&'a FlexRect<f32>);
+
+impl std::fmt::Display for Edges<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let edges = [
+            (self.0.start, "left"),
+            (self.0.end, "right"),
+            (self.0.top, "top"),
+            (self.0.bottom, "bottom"),
+        ];
+        let edges = edges.iter().filter(|(overflow, _)| *overflow > EPSILON);
+        for (i, (overflow, edge)) in edges.enumerate() {
+            let separator = if i == 0 { "" } else { ", " };
+            write!(f, "{separator}{overflow} {edge}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_labels_under_their_node() {
+        let points = Some(FlexDimension::Points(10.0));
+        let node = FlexNode {
+            label: Some("longer than its box".to_string()),
+            layout: FlexLayout {
+                size: FlexSize { width: points, height: points },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let root = FlexNode { nodes: vec![node], ..Default::default() };
+        let root = crate::compute_layout(root, None).unwrap();
+        let diagnostics = diagnose(&root);
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        let diagnostic = &diagnostics[0];
+        assert_eq!(
+            (diagnostic.path.as_slice(), diagnostic.label),
+            (&[0][..], true)
+        );
+        assert!(diagnostic.to_string().starts_with("nodes[0].label <text>: "));
+    }
+}
@@ -0,0 +1,679 @@
+use crate::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::Write;
+
+// @NOTE(jshrake): Each bend costs as much as this much length, so routes
+// prefer a short detour to a staircase
+const BEND_PENALTY: f32 = 20.0;
+
+/// Draws the root `edges` between the laid out boxes of the nodes they name
+pub(crate) fn compute_edges_string(
+    root: &FlexGeomNode,
+    root_data: &FlexRootData,
+) -> Result<String, Error> {
+    let mut svg = String::new();
+    let edges = &root_data.edges;
+    if edges.is_empty() {
+        return Ok(svg);
+    }
+    let mut boxes = vec![];
+    collect_boxes(root, &mut vec![], &mut boxes);
+    let mut ids = HashMap::new();
+    // @NOTE(jshrake): The first node with an id wins, like getElementById
+    for node_box in boxes.iter().rev() {
+        if let Some(id) = node_box.id {
+            ids.insert(id, node_box);
+        }
+    }
+    let node_box = |id: &String| {
+        ids.get(id.as_str())
+            .copied()
+            .ok_or_else(|| Error::UnknownId(id.clone()))
+    };
+    writeln!(
+        svg,
+        r#"<defs><marker id="flexvg-arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="4" markerHeight="4" orient="auto"><path d="M0 0L10 5L0 10Z" /></marker></defs>"#
+    )
+    .unwrap();
+    let spacing = root_data.edge_spacing;
+    // @NOTE(jshrake): Routes stay within the root node, which the viewBox
+    // shows by default
+    let bounds = Rect {
+        x: root.absolute_position.x,
+        y: root.absolute_position.y,
+        width: root.width,
+        height: root.height,
+    };
+    let offsets = parallel_offsets(edges, spacing);
+    for (edge, (offset, spread)) in edges.iter().zip(offsets) {
+        if edge.from == edge.to {
+            return Err(Error::SelfEdge(edge.from.clone()));
+        }
+        let (from, to) = (node_box(&edge.from)?, node_box(&edge.to)?);
+        let points = match edge.routing {
+            FlexRouting::Straight => None,
+            FlexRouting::Orthogonal => {
+                let obstacles: Vec<_> = boxes
+                    .iter()
+                    .filter(|other| other.is_obstacle(from, to))
+                    .map(|other| other.rect)
+                    .collect();
+                // @NOTE(jshrake): Parallel edges all keep `spacing` clear
+                // of the boxes once moved apart
+                let clearance = spacing + spread;
+                // @NOTE(jshrake): Edges in either direction take the same
+                // route, so that parallel edges stay side by side
+                let forward = edge.from < edge.to;
+                let (start, end) =
+                    if forward { (from, to) } else { (to, from) };
+                orthogonal_edge(
+                    start.rect, end.rect, &obstacles, clearance, bounds,
+                )
+                .map(|points| {
+                    let mut points = offset_orthogonal(points, offset);
+                    if !forward {
+                        points.reverse();
+                    }
+                    points
+                })
+            }
+        };
+        let points = points.unwrap_or_else(|| {
+            let (start, end) = straight_edge(from.rect, to.rect);
+            offset_straight(start, end, offset, edge.from < edge.to)
+        });
+        write_edge(&mut svg, edge, &points);
+    }
+    Ok(svg)
+}
+
+/// Box of a node relative to the root node, ignoring its transform
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Rect {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+impl Rect {
+    fn center(&self) -> FlexPoint {
+        FlexPoint {
+            x: self.x + 0.5 * self.width,
+            y: self.y + 0.5 * self.height,
+        }
+    }
+
+    fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    /// Whether the point is inside the box or on its border
+    fn contains(&self, point: FlexPoint) -> bool {
+        (self.x..=self.right()).contains(&point.x)
+            && (self.y..=self.bottom()).contains(&point.y)
+    }
+
+    /// Whether the segment passes through the inside of the box, not just
+    /// along its border
+    fn crosses(&self, a: FlexPoint, b: FlexPoint) -> bool {
+        a.x.min(b.x) < self.right()
+            && a.x.max(b.x) > self.x
+            && a.y.min(b.y) < self.bottom()
+            && a.y.max(b.y) > self.y
+    }
+
+    /// Where a line from the center toward `target` crosses the border
+    fn exit(&self, target: FlexPoint) -> FlexPoint {
+        let center = self.center();
+        let (dx, dy) = (target.x - center.x, target.y - center.y);
+        let scale = |half: f32, delta: f32| {
+            if delta == 0.0 {
+                f32::INFINITY
+            } else {
+                half / delta.abs()
+            }
+        };
+        let t = scale(0.5 * self.width, dx).min(scale(0.5 * self.height, dy));
+        if !t.is_finite() {
+            return center;
+        }
+        FlexPoint { x: center.x + t * dx, y: center.y + t * dy }
+    }
+}
+
+/// A visible node: one with an id, an element or a decoration
+struct NodeBox<'a> {
+    id: Option<&'a str>,
+    path: Vec<usize>,
+    rect: Rect,
+}
+
+impl NodeBox<'_> {
+    /// Boxes around, inside or at either end of an edge aren't in its way
+    fn is_obstacle(&self, from: &NodeBox, to: &NodeBox) -> bool {
+        let related = |other: &NodeBox| {
+            self.path.starts_with(&other.path)
+                || other.path.starts_with(&self.path)
+        };
+        self.rect.width > 0.0
+            && self.rect.height > 0.0
+            && !related(from)
+            && !related(to)
+    }
+}
+
+fn collect_boxes<'a>(
+    node: &'a FlexGeomNode,
+    path: &mut Vec<usize>,
+    boxes: &mut Vec<NodeBox<'a>>,
+) {
+    let decoration = &node.decoration;
+    let is_visible = node.id.is_some()
+        || node.element.is_some()
+        || decoration.background.is_some()
+        || decoration.border().is_some();
+    if is_visible {
+        boxes.push(NodeBox {
+            id: node.id.as_deref(),
+            path: path.clone(),
+            rect: Rect {
+                x: node.absolute_position.x,
+                y: node.absolute_position.y,
+                width: node.width,
+                height: node.height,
+            },
+        });
+    }
+    for (i, child) in node.nodes.iter().enumerate() {
+        path.push(i);
+        collect_boxes(child, path, boxes);
+        path.pop();
+    }
+}
+
+/// Separates edges joining the same pair of nodes, in either direction, by
+/// `spacing`, centered on the edge they would otherwise share. Also returns
+/// how far the outermost edge of each group is moved.
+fn parallel_offsets<'a>(
+    edges: &'a [FlexEdge],
+    spacing: f32,
+) -> Vec<(f32, f32)> {
+    let pair = |edge: &'a FlexEdge| -> (&'a str, &'a str) {
+        let (a, b) = (edge.from.as_str(), edge.to.as_str());
+        if a < b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    };
+    let mut counts = HashMap::new();
+    for edge in edges {
+        *counts.entry(pair(edge)).or_insert(0) += 1;
+    }
+    let mut seen = HashMap::new();
+    edges
+        .iter()
+        .map(|edge| {
+            let count = counts[&pair(edge)] as f32;
+            let index = seen.entry(pair(edge)).or_insert(0);
+            let spread = 0.5 * (count - 1.0) * spacing;
+            let offset = *index as f32 * spacing - spread;
+            *index += 1;
+            (offset, spread)
+        })
+        .collect()
+}
+
+/// A line between the centers of the boxes, clipped at their borders.
+/// Overlapping boxes are joined center to center.
+fn straight_edge(from: Rect, to: Rect) -> (FlexPoint, FlexPoint) {
+    let (from_center, to_center) = (from.center(), to.center());
+    let start = from.exit(to_center);
+    let end = to.exit(from_center);
+    let forward = (end.x - start.x) * (to_center.x - from_center.x)
+        + (end.y - start.y) * (to_center.y - from_center.y);
+    if forward > 0.0 {
+        (start, end)
+    } else {
+        (from_center, to_center)
+    }
+}
+
+/// Moves a straight edge sideways. `forward` tells edges between the same
+/// nodes in opposite directions apart, so they move the same way.
+fn offset_straight(
+    start: FlexPoint,
+    end: FlexPoint,
+    offset: f32,
+    forward: bool,
+) -> Vec<FlexPoint> {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let length = dx.hypot(dy);
+    if offset == 0.0 || length == 0.0 {
+        return vec![start, end];
+    }
+    let sign = if forward { 1.0 } else { -1.0 };
+    let (nx, ny) = (-dy / length * offset * sign, dx / length * offset * sign);
+    let shift = |p: FlexPoint| FlexPoint { x: p.x + nx, y: p.y + ny };
+    vec![shift(start), shift(end)]
+}
+
+/// Moves every segment of an orthogonal edge sideways by `offset`, like
+/// `offset_straight`, so that parallel routes stay nested around bends.
+/// The ends only move along the borders they leave and enter.
+fn offset_orthogonal(points: Vec<FlexPoint>, offset: f32) -> Vec<FlexPoint> {
+    if points.len() < 2 {
+        return points;
+    }
+    // @NOTE(jshrake): Points only remain at bends, so each segment is
+    // either horizontal or vertical and a bend joins one of each
+    let shifts: Vec<_> = points
+        .windows(2)
+        .map(|w| {
+            let dx = (w[1].x - w[0].x).signum() * offset;
+            let dy = (w[1].y - w[0].y).signum() * offset;
+            if w[0].y == w[1].y {
+                (0.0, dx)
+            } else {
+                (-dy, 0.0)
+            }
+        })
+        .collect();
+    let last = shifts.len() - 1;
+    let shift = |i: usize| {
+        let (before, after) =
+            (shifts[i.saturating_sub(1)], shifts[i.min(last)]);
+        match i {
+            0 => after,
+            i if i > last => before,
+            _ => (before.0 + after.0, before.1 + after.1),
+        }
+    };
+    let shifted = points.iter().enumerate().map(|(i, point)| {
+        let (x, y) = shift(i);
+        FlexPoint { x: point.x + x, y: point.y + y }
+    });
+    shifted.collect()
+}
+
+/// Finds the horizontal and vertical segments with the fewest bends and
+/// shortest length from the center of `from` to the center of `to`, on a
+/// grid through the sides of the obstacles, kept `clearance` away, and the
+/// centers. Returns `None` when the obstacles block every route inside
+/// `bounds`.
+fn orthogonal_edge(
+    from: Rect,
+    to: Rect,
+    obstacles: &[Rect],
+    clearance: f32,
+    bounds: Rect,
+) -> Option<Vec<FlexPoint>> {
+    let (start, end) = (from.center(), to.center());
+    let mut xs = vec![start.x, end.x];
+    let mut ys = vec![start.y, end.y];
+    for rect in obstacles.iter().chain([&from, &to]) {
+        xs.extend([rect.x - clearance, rect.right() + clearance]);
+        ys.extend([rect.y - clearance, rect.bottom() + clearance]);
+    }
+    xs.retain(|x| (bounds.x..=bounds.right()).contains(x));
+    ys.retain(|y| (bounds.y..=bounds.bottom()).contains(y));
+    for coordinates in [&mut xs, &mut ys] {
+        coordinates.sort_by(f32::total_cmp);
+        coordinates.dedup();
+    }
+    let grid = Grid { xs, ys, obstacles };
+    let route = grid.route(grid.index(start)?, grid.index(end)?)?;
+    clip(&route, from, to)
+}
+
+struct Grid<'a> {
+    xs: Vec<f32>,
+    ys: Vec<f32>,
+    obstacles: &'a [Rect],
+}
+
+/// A search state: a grid point, and the direction it was entered from
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct State {
+    cost: f32,
+    point: (usize, usize),
+    direction: usize,
+}
+
+impl Eq for State {}
+
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // @NOTE(jshrake): Reversed, so the heap pops the cheapest state
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+const DIRECTIONS: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+// @NOTE(jshrake): The start point wasn't entered from any direction
+const NO_DIRECTION: usize = DIRECTIONS.len();
+
+impl Grid<'_> {
+    fn index(&self, point: FlexPoint) -> Option<(usize, usize)> {
+        let i = self.xs.iter().position(|x| *x == point.x)?;
+        let j = self.ys.iter().position(|y| *y == point.y)?;
+        Some((i, j))
+    }
+
+    fn point(&self, (i, j): (usize, usize)) -> FlexPoint {
+        FlexPoint { x: self.xs[i], y: self.ys[j] }
+    }
+
+    fn neighbor(
+        &self,
+        (i, j): (usize, usize),
+        direction: usize,
+    ) -> Option<(usize, usize)> {
+        let (di, dj) = DIRECTIONS[direction];
+        let i = i.checked_add_signed(di).filter(|i| *i < self.xs.len())?;
+        let j = j.checked_add_signed(dj).filter(|j| *j < self.ys.len())?;
+        Some((i, j))
+    }
+
+    /// Dijkstra's algorithm over points and directions, so that bends can
+    /// be charged for
+    fn route(
+        &self,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> Option<Vec<FlexPoint>> {
+        let states = (DIRECTIONS.len() + 1) * self.xs.len() * self.ys.len();
+        let key = |(i, j): (usize, usize), direction: usize| {
+            (j * self.xs.len() + i) * (DIRECTIONS.len() + 1) + direction
+        };
+        let mut costs = vec![f32::INFINITY; states];
+        let mut previous = vec![None; states];
+        let mut heap = BinaryHeap::new();
+        costs[key(start, NO_DIRECTION)] = 0.0;
+        heap.push(State { cost: 0.0, point: start, direction: NO_DIRECTION });
+        while let Some(State { cost, point, direction }) = heap.pop() {
+            if point == end {
+                return Some(self.path(&previous, key, (point, direction)));
+            }
+            if cost > costs[key(point, direction)] {
+                continue;
+            }
+            for next_direction in 0..DIRECTIONS.len() {
+                let next = match self.neighbor(point, next_direction) {
+                    Some(next) => next,
+                    None => continue,
+                };
+                let (a, b) = (self.point(point), self.point(next));
+                if self.obstacles.iter().any(|rect| rect.crosses(a, b)) {
+                    continue;
+                }
+                let turns =
+                    direction != NO_DIRECTION && direction != next_direction;
+                let bend = if turns { BEND_PENALTY } else { 0.0 };
+                let next_cost =
+                    cost + (b.x - a.x).abs() + (b.y - a.y).abs() + bend;
+                let next_key = key(next, next_direction);
+                if next_cost < costs[next_key] {
+                    costs[next_key] = next_cost;
+                    previous[next_key] = Some((point, direction));
+                    heap.push(State {
+                        cost: next_cost,
+                        point: next,
+                        direction: next_direction,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Walks back from the end, keeping only the ends and bends
+    fn path(
+        &self,
+        previous: &[Option<((usize, usize), usize)>],
+        key: impl Fn((usize, usize), usize) -> usize,
+        end: ((usize, usize), usize),
+    ) -> Vec<FlexPoint> {
+        let mut points = vec![self.point(end.0)];
+        let mut state = end;
+        while let Some(before) = previous[key(state.0, state.1)] {
+            if before.1 != state.1
+                || previous[key(before.0, before.1)].is_none()
+            {
+                points.push(self.point(before.0));
+            }
+            state = before;
+        }
+        points.reverse();
+        points
+    }
+}
+
+/// Cuts a route between two centers at the border it last leaves `from`
+/// through and the border it first enters `to` through
+fn clip(route: &[FlexPoint], from: Rect, to: Rect) -> Option<Vec<FlexPoint>> {
+    let exit = route.iter().rposition(|point| from.contains(*point))?;
+    let entry = route.iter().position(|point| to.contains(*point))?;
+    if entry <= exit {
+        return None;
+    }
+    let border = |inside: FlexPoint, outside: FlexPoint, rect: Rect| {
+        let x = match outside.x.partial_cmp(&inside.x) {
+            Some(Ordering::Greater) => rect.right(),
+            Some(Ordering::Less) => rect.x,
+            _ => inside.x,
+        };
+        let y = match outside.y.partial_cmp(&inside.y) {
+            Some(Ordering::Greater) => rect.bottom(),
+            Some(Ordering::Less) => rect.y,
+            _ => inside.y,
+        };
+        FlexPoint { x, y }
+    };
+    let mut points = vec![border(route[exit], route[exit + 1], from)];
+    points.extend_from_slice(&route[exit + 1..entry]);
+    points.push(border(route[entry], route[entry - 1], to));
+    Some(points)
+}
+
+fn write_edge(svg: &mut String, edge: &FlexEdge, points: &[FlexPoint]) {
+    let class = edge.class.as_deref().map(|class| format!(" {class}"));
+    writeln!(svg, r#"<g class="flexvg-edge{}">"#, class.unwrap_or_default())
+        .unwrap();
+    let d = rounded_path(points, edge.radius);
+    writeln!(
+        svg,
+        r#"<path d="{d}" fill="none" stroke="black" marker-end="url(#flexvg-arrow)" />"#
+    )
+    .unwrap();
+    if let Some(label) = &edge.label {
+        let FlexPoint { x, y } = midpoint(points);
+        writeln!(
+            svg,
+            r#"<text x="{x}" y="{y}" text-anchor="middle" dominant-baseline="middle">{label}</text>"#
+        )
+        .unwrap();
+    }
+    writeln!(svg, "</g>").unwrap();
+}
+
+/// Path data through the points, with each bend rounded by an arc of at
+/// most `radius`
+fn rounded_path(points: &[FlexPoint], radius: f32) -> String {
+    let mut d = String::new();
+    let length = |a: FlexPoint, b: FlexPoint| (b.x - a.x).hypot(b.y - a.y);
+    let toward = |from: FlexPoint, to: FlexPoint, distance: f32| {
+        let t = distance / length(from, to);
+        FlexPoint {
+            x: from.x + t * (to.x - from.x),
+            y: from.y + t * (to.y - from.y),
+        }
+    };
+    for (i, point) in points.iter().enumerate() {
+        let (before, after) = match (i.checked_sub(1), points.get(i + 1)) {
+            (Some(before), Some(after)) if radius > 0.0 => {
+                (points[before], *after)
+            }
+            _ => {
+                let command = if i == 0 { 'M' } else { 'L' };
+                write!(d, "{command}{} {}", point.x, point.y).unwrap();
+                continue;
+            }
+        };
+        // @NOTE(jshrake): Each bend gets at most half of its segments
+        let r = radius
+            .min(0.5 * length(before, *point))
+            .min(0.5 * length(*point, after));
+        if r <= 0.0 {
+            write!(d, "L{} {}", point.x, point.y).unwrap();
+            continue;
+        }
+        let (a, b) = (toward(*point, before, r), toward(*point, after, r));
+        let cross = (point.x - before.x) * (after.y - point.y)
+            - (point.y - before.y) * (after.x - point.x);
+        let sweep = u8::from(cross > 0.0);
+        write!(d, "L{} {}A{r} {r} 0 0 {sweep} {} {}", a.x, a.y, b.x, b.y)
+            .unwrap();
+    }
+    d
+}
+
+/// The point halfway along a polyline
+fn midpoint(points: &[FlexPoint]) -> FlexPoint {
+    let length = |a: &FlexPoint, b: &FlexPoint| (b.x - a.x).hypot(b.y - a.y);
+    let total: f32 = points.windows(2).map(|w| length(&w[0], &w[1])).sum();
+    let mut remaining = 0.5 * total;
+    for w in points.windows(2) {
+        let segment = length(&w[0], &w[1]);
+        if segment > 0.0 && remaining <= segment {
+            let t = remaining / segment;
+            return FlexPoint {
+                x: w[0].x + t * (w[1].x - w[0].x),
+                y: w[0].y + t * (w[1].y - w[0].y),
+            };
+        }
+        remaining -= segment;
+    }
+    points.first().copied().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str) -> FlexNode {
+        FlexNode { id: Some(id.to_string()), ..Default::default() }
+    }
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    fn points(points: &[(f32, f32)]) -> Vec<FlexPoint> {
+        points.iter().map(|&(x, y)| FlexPoint { x, y }).collect()
+    }
+
+    const BOUNDS: Rect = Rect { x: 0.0, y: 0.0, width: 100.0, height: 100.0 };
+
+    #[test]
+    fn routes_around_an_obstacle() {
+        let (from, to) =
+            (rect(10.0, 40.0, 10.0, 10.0), rect(80.0, 40.0, 10.0, 10.0));
+        let obstacles = [rect(40.0, 30.0, 20.0, 40.0)];
+        let route = orthogonal_edge(from, to, &obstacles, 5.0, BOUNDS);
+        let expected =
+            points(&[(15.0, 40.0), (15.0, 25.0), (85.0, 25.0), (85.0, 40.0)]);
+        assert_eq!(route, Some(expected));
+        // @NOTE(jshrake): Bends cost the same without clearance, so the
+        // route doesn't turn into a staircase
+        let route = orthogonal_edge(from, to, &obstacles, 0.0, BOUNDS);
+        let expected =
+            points(&[(15.0, 40.0), (15.0, 30.0), (85.0, 30.0), (85.0, 40.0)]);
+        assert_eq!(route, Some(expected));
+        // @NOTE(jshrake): With nothing in the way, the route is straight
+        let route = orthogonal_edge(from, to, &[], 5.0, BOUNDS);
+        assert_eq!(route, Some(points(&[(20.0, 45.0), (80.0, 45.0)])));
+    }
+
+    #[test]
+    fn stays_inside_the_bounds() {
+        let (from, to) =
+            (rect(0.0, 0.0, 10.0, 10.0), rect(40.0, 0.0, 10.0, 10.0));
+        let obstacles = [rect(20.0, 0.0, 10.0, 20.0)];
+        let route = orthogonal_edge(from, to, &obstacles, 5.0, BOUNDS).unwrap();
+        assert!(route.iter().all(|point| BOUNDS.contains(*point)), "{route:?}");
+        assert_eq!(route[1], FlexPoint { x: 5.0, y: 25.0 });
+    }
+
+    #[test]
+    fn gives_up_when_blocked() {
+        let (from, to) =
+            (rect(10.0, 40.0, 10.0, 10.0), rect(80.0, 40.0, 10.0, 10.0));
+        let wall = [rect(40.0, 0.0, 20.0, 100.0)];
+        assert_eq!(orthogonal_edge(from, to, &wall, 5.0, BOUNDS), None);
+        // @NOTE(jshrake): Centers outside the bounds aren't on the grid
+        let outside = rect(120.0, 40.0, 10.0, 10.0);
+        assert_eq!(orthogonal_edge(from, outside, &[], 5.0, BOUNDS), None);
+    }
+
+    #[test]
+    fn nests_edges_in_opposite_directions() {
+        let edge = |from: &str, to: &str| FlexEdge {
+            from: from.into(),
+            to: to.into(),
+            ..Default::default()
+        };
+        let edges = [edge("a", "b"), edge("b", "a")];
+        let offsets = parallel_offsets(&edges, 10.0);
+        assert_eq!(offsets, vec![(-5.0, 5.0), (5.0, 5.0)]);
+        // @NOTE(jshrake): Both edges are routed from `a` to `b`, and the
+        // second is reversed once moved aside
+        let route = points(&[(0.0, 0.0), (50.0, 0.0), (50.0, 50.0)]);
+        let a_to_b = offset_orthogonal(route.clone(), offsets[0].0);
+        let mut b_to_a = offset_orthogonal(route, offsets[1].0);
+        b_to_a.reverse();
+        assert_eq!(a_to_b, points(&[(0.0, -5.0), (55.0, -5.0), (55.0, 50.0)]));
+        assert_eq!(b_to_a, points(&[(45.0, 50.0), (45.0, 5.0), (0.0, 5.0)]));
+        let single = points(&[(1.0, 2.0)]);
+        assert_eq!(offset_orthogonal(single.clone(), 5.0), single);
+        assert_eq!(offset_orthogonal(vec![], 5.0), vec![]);
+    }
+
+    #[test]
+    fn rounds_bends() {
+        let route =
+            points(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+        assert_eq!(
+            rounded_path(&route, 2.0),
+            "M0 0L8 0A2 2 0 0 1 10 2L10 8A2 2 0 0 1 8 10L0 10"
+        );
+        assert_eq!(rounded_path(&route, 0.0), "M0 0L10 0L10 10L0 10");
+        // @NOTE(jshrake): The arc takes at most half of a short segment
+        let route = points(&[(0.0, 0.0), (2.0, 0.0), (2.0, -10.0)]);
+        assert_eq!(rounded_path(&route, 4.0), "M0 0L1 0A1 1 0 0 0 2 -1L2 -10");
+    }
+
+    #[test]
+    fn rejects_edges_to_the_same_node() {
+        let edge =
+            FlexEdge { from: "a".into(), to: "a".into(), ..Default::default() };
+        let root_data =
+            FlexRootData { edges: vec![edge], ..Default::default() };
+        let root = FlexNode { nodes: vec![node("a")], ..Default::default() };
+        let root = crate::compute_layout(root, None).unwrap();
+        let error = compute_edges_string(&root, &root_data).unwrap_err();
+        assert!(matches!(error, Error::SelfEdge(id) if id == "a"));
+    }
+}
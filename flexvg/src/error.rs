@@ -3,9 +3,17 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 pub enum FlexError {
     #[error("Taffy error")]
-    Taffy(#[from] taffy::Error),
+    Taffy(#[from] taffy::error::TaffyError),
     #[error("File IO error")]
     IO(#[from] std::io::Error),
+    #[error("Font parsing error")]
+    Font(#[from] ttf_parser::FaceParsingError),
+    #[error("Path data error: {0}")]
+    Path(String),
+    #[error("Edge refers to unknown node id `{0}`")]
+    UnknownId(String),
+    #[error("Edge from node id `{0}` to itself")]
+    SelfEdge(String),
     #[error("unknown error")]
     Unknown,
 }
@@ -0,0 +1,304 @@
+use crate::prelude::*;
+use log::warn;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use taffy::prelude::*;
+
+/// Places children that name an area of their parent's
+/// `grid-template-areas` on that area's lines. An explicit `grid-row` or
+/// `grid-column` is kept.
+pub(crate) fn place_grid_areas(parent: &FlexLayout, children: &mut [FlexNode]) {
+    if parent.grid_template_areas.is_empty() {
+        return;
+    }
+    let areas = grid_areas(&parent.grid_template_areas);
+    for child in children {
+        let Some(name) = child.layout.grid_area.clone() else {
+            continue;
+        };
+        match areas.get(&name) {
+            Some((row, column)) => {
+                let layout = &mut child.layout;
+                let placements = [
+                    (&mut layout.grid_row, row, "grid-row"),
+                    (&mut layout.grid_column, column, "grid-column"),
+                ];
+                for (placement, area, property) in placements {
+                    if is_auto(placement) {
+                        *placement = *area;
+                    } else {
+                        warn!("[grid] {property} overrides grid-area {name}");
+                    }
+                }
+            }
+            None => warn!("[grid] unknown grid-area {name}"),
+        }
+    }
+}
+
+fn is_auto(placement: &Line<GridPlacement>) -> bool {
+    placement.start == GridPlacement::Auto
+        && placement.end == GridPlacement::Auto
+}
+
+/// Row and column lines spanned by each named area. `.` marks an unnamed
+/// cell.
+fn grid_areas(
+    rows: &[String],
+) -> HashMap<String, (Line<GridPlacement>, Line<GridPlacement>)> {
+    let mut bounds: HashMap<&str, (usize, usize, usize, usize)> =
+        HashMap::new();
+    for (row, cells) in rows.iter().enumerate() {
+        for (column, name) in cells.split_whitespace().enumerate() {
+            if name.chars().all(|c| c == '.') {
+                continue;
+            }
+            let bound =
+                bounds.entry(name).or_insert((row, row, column, column));
+            bound.0 = bound.0.min(row);
+            bound.1 = bound.1.max(row);
+            bound.2 = bound.2.min(column);
+            bound.3 = bound.3.max(column);
+        }
+    }
+    // @NOTE(jshrake): Grid lines are 1-based and the end line is exclusive
+    let lines = |first: usize, last: usize| Line {
+        start: line(first as i16 + 1),
+        end: line(last as i16 + 2),
+    };
+    bounds
+        .into_iter()
+        .map(|(name, (top, bottom, left, right))| {
+            (name.to_string(), (lines(top, bottom), lines(left, right)))
+        })
+        .collect()
+}
+
+/// Parses a `grid-template-rows` or `grid-template-columns` track list,
+/// e.g. `100pt repeat(2, 1fr) minmax(10%, auto)`.
+#[cfg(feature = "serde")]
+fn parse_track_list(s: &str) -> Result<Vec<TrackSizingFunction>, String> {
+    split_tokens(s, char::is_whitespace)
+        .into_iter()
+        .map(|token| {
+            let Some(args) = function_args(token, "repeat") else {
+                return parse_track(token).map(TrackSizingFunction::Single);
+            };
+            let (count, tracks) = args
+                .split_once(',')
+                .ok_or_else(|| format!("invalid repeat `{token}`"))?;
+            let count = match count.trim() {
+                "auto-fill" => GridTrackRepetition::AutoFill,
+                "auto-fit" => GridTrackRepetition::AutoFit,
+                count => count
+                    .parse()
+                    .map(GridTrackRepetition::Count)
+                    .map_err(|_| format!("invalid repeat count `{count}`"))?,
+            };
+            Ok(TrackSizingFunction::Repeat(
+                count,
+                parse_auto_track_list(tracks)?,
+            ))
+        })
+        .collect()
+}
+
+/// Parses a `grid-auto-rows` or `grid-auto-columns` track list
+#[cfg(feature = "serde")]
+fn parse_auto_track_list(
+    s: &str,
+) -> Result<Vec<NonRepeatedTrackSizingFunction>, String> {
+    split_tokens(s, char::is_whitespace).into_iter().map(parse_track).collect()
+}
+
+#[cfg(feature = "serde")]
+fn parse_track(token: &str) -> Result<NonRepeatedTrackSizingFunction, String> {
+    if let Some(args) = function_args(token, "minmax") {
+        let (min, max) = args
+            .split_once(',')
+            .ok_or_else(|| format!("invalid minmax `{token}`"))?;
+        return Ok(minmax(
+            parse_min_track(min.trim())?,
+            parse_max_track(max.trim())?,
+        ));
+    }
+    let min = match parse_min_track(token) {
+        Ok(min) => min,
+        // fr and fit-content() only size the maximum
+        Err(_) => MinTrackSizingFunction::Auto,
+    };
+    Ok(minmax(min, parse_max_track(token)?))
+}
+
+#[cfg(feature = "serde")]
+fn parse_min_track(token: &str) -> Result<MinTrackSizingFunction, String> {
+    match token {
+        "auto" => Ok(MinTrackSizingFunction::Auto),
+        "min-content" => Ok(MinTrackSizingFunction::MinContent),
+        "max-content" => Ok(MinTrackSizingFunction::MaxContent),
+        _ => parse_length_percentage(token).map(MinTrackSizingFunction::Fixed),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn parse_max_track(token: &str) -> Result<MaxTrackSizingFunction, String> {
+    if let Some(args) = function_args(token, "fit-content") {
+        return parse_length_percentage(args.trim())
+            .map(MaxTrackSizingFunction::FitContent);
+    }
+    if let Some(fraction) = token.strip_suffix("fr") {
+        return fraction
+            .parse()
+            .map(MaxTrackSizingFunction::Fraction)
+            .map_err(|_| format!("invalid track size `{token}`"));
+    }
+    match token {
+        "auto" => Ok(MaxTrackSizingFunction::Auto),
+        "min-content" => Ok(MaxTrackSizingFunction::MinContent),
+        "max-content" => Ok(MaxTrackSizingFunction::MaxContent),
+        _ => parse_length_percentage(token).map(MaxTrackSizingFunction::Fixed),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn parse_length_percentage(token: &str) -> Result<LengthPercentage, String> {
+    let invalid = || format!("invalid track size `{token}`");
+    if let Some(percent) = token.strip_suffix('%') {
+        let percent: f32 = percent.parse().map_err(|_| invalid())?;
+        return Ok(LengthPercentage::Percent(percent / 100.0));
+    }
+    let points = token
+        .strip_suffix("pt")
+        .or_else(|| token.strip_suffix("px"))
+        .unwrap_or(token);
+    points.parse().map(LengthPercentage::Points).map_err(|_| invalid())
+}
+
+/// Parses `grid-row` or `grid-column`: `auto`, `2`, `span 2`, `1 / 3`,
+/// `2 / span 2`, `-1`.
+#[cfg(feature = "serde")]
+fn parse_placement(s: &str) -> Result<Line<GridPlacement>, String> {
+    let mut lines = s.split('/').map(str::trim);
+    let start = parse_grid_line(lines.next().unwrap_or("auto"))?;
+    let end = parse_grid_line(lines.next().unwrap_or("auto"))?;
+    if lines.next().is_some() {
+        return Err(format!("invalid grid placement `{s}`"));
+    }
+    Ok(Line { start, end })
+}
+
+#[cfg(feature = "serde")]
+fn parse_grid_line(s: &str) -> Result<GridPlacement, String> {
+    if s == "auto" {
+        return Ok(GridPlacement::Auto);
+    }
+    if let Some(count) = s.strip_prefix("span") {
+        return count
+            .trim()
+            .parse()
+            .map(GridPlacement::Span)
+            .map_err(|_| format!("invalid grid span `{s}`"));
+    }
+    s.parse::<i16>().map(line).map_err(|_| format!("invalid grid line `{s}`"))
+}
+
+/// Returns the arguments of `name(...)`
+#[cfg(feature = "serde")]
+fn function_args<'a>(token: &'a str, name: &str) -> Option<&'a str> {
+    token.strip_prefix(name)?.strip_prefix('(')?.strip_suffix(')')
+}
+
+/// Splits on `separator`, ignoring separators nested in parentheses
+#[cfg(feature = "serde")]
+pub(crate) fn split_tokens(
+    s: &str,
+    separator: impl Fn(char) -> bool,
+) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if depth == 0 && separator(c) => {
+                tokens.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    tokens.push(&s[start..]);
+    tokens.into_iter().map(str::trim).filter(|t| !t.is_empty()).collect()
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_track_list<'de, D>(
+    deserializer: D,
+) -> Result<Vec<TrackSizingFunction>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    parse_track_list(&s).map_err(serde::de::Error::custom)
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_auto_track_list<'de, D>(
+    deserializer: D,
+) -> Result<Vec<NonRepeatedTrackSizingFunction>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    parse_auto_track_list(&s).map_err(serde::de::Error::custom)
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber {
+    String(String),
+    Number(i16),
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_placement<'de, D>(
+    deserializer: D,
+) -> Result<Line<GridPlacement>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(s) => {
+            parse_placement(&s).map_err(serde::de::Error::custom)
+        }
+        StringOrNumber::Number(n) => Ok(Line { start: line(n), end: auto() }),
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrList {
+    String(String),
+    List(Vec<String>),
+}
+
+/// Accepts a list of rows, or the CSS form `"a a" "b c"`
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_template_areas<'de, D>(
+    deserializer: D,
+) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match StringOrList::deserialize(deserializer)? {
+        StringOrList::List(rows) => rows,
+        StringOrList::String(s) if s.contains('"') => {
+            s.split('"').skip(1).step_by(2).map(str::to_string).collect()
+        }
+        StringOrList::String(s) => s.lines().map(str::to_string).collect(),
+    })
+}
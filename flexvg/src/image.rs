@@ -0,0 +1,115 @@
+use crate::prelude::*;
+use log::warn;
+use std::{fs, path::Path};
+use taffy::prelude::AvailableSpace as TaffyAvailableSpace;
+use taffy::prelude::Size as TaffySize;
+
+/// Natural size of the file referenced by an `image` element.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct FlexImage {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl FlexImage {
+    /// Reads the natural size of an `image` element's `href`, relative to
+    /// `base_path`. Remote and data URLs have no intrinsic size here.
+    pub fn load(
+        element: &FlexElement,
+        base_path: Option<&Path>,
+    ) -> Option<FlexImage> {
+        if element.element != "image" {
+            return None;
+        }
+        let href = element
+            .attributes
+            .get("href")
+            .or_else(|| element.attributes.get("xlink:href"))?
+            .to_string();
+        if href.contains("://") || href.starts_with("data:") {
+            return None;
+        }
+        let path = if let Some(base_path) = base_path {
+            base_path.join(&href)
+        } else {
+            Path::new(&href).to_path_buf()
+        };
+        let image = if href.ends_with(".svg") {
+            fs::read_to_string(&path)
+                .ok()
+                .and_then(|contents| svg_size(&contents))
+        } else {
+            imagesize::size(&path).ok().map(|size| FlexImage {
+                width: size.width as f32,
+                height: size.height as f32,
+            })
+        };
+        let image =
+            image.filter(|image| image.width > 0.0 && image.height > 0.0);
+        if image.is_none() {
+            warn!("[image] unable to read the size of {}", path.display());
+        }
+        image
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width / self.height
+    }
+
+    /// Taffy measure function: the natural size, scaled to keep the aspect
+    /// ratio when one dimension is already known.
+    pub fn measure(
+        &self,
+        known_dimensions: TaffySize<Option<f32>>,
+        _available_space: TaffySize<TaffyAvailableSpace>,
+    ) -> TaffySize<f32> {
+        match (known_dimensions.width, known_dimensions.height) {
+            (Some(width), Some(height)) => TaffySize { width, height },
+            (Some(width), None) => {
+                TaffySize { width, height: width / self.aspect_ratio() }
+            }
+            (None, Some(height)) => {
+                TaffySize { width: height * self.aspect_ratio(), height }
+            }
+            (None, None) => {
+                TaffySize { width: self.width, height: self.height }
+            }
+        }
+    }
+}
+
+/// Size of an SVG document from the `width` and `height` of its root
+/// element, falling back to its `viewBox`.
+fn svg_size(contents: &str) -> Option<FlexImage> {
+    let start = contents.find("<svg")?;
+    let end = start + contents[start..].find('>')?;
+    let tag = &contents[start..end];
+    let length = |name| {
+        svg_attribute(tag, name).and_then(|value| {
+            value.trim().trim_end_matches("px").parse::<f32>().ok()
+        })
+    };
+    if let (Some(width), Some(height)) = (length("width"), length("height")) {
+        return Some(FlexImage { width, height });
+    }
+    let view_box: Vec<f32> = svg_attribute(tag, "viewBox")?
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .filter_map(|value| value.parse().ok())
+        .collect();
+    match view_box[..] {
+        [_, _, width, height] => Some(FlexImage { width, height }),
+        _ => None,
+    }
+}
+
+fn svg_attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    tag.match_indices(name).find_map(|(i, _)| {
+        let preceded_by_space = tag[..i].ends_with(char::is_whitespace);
+        let rest = tag[i + name.len()..].trim_start().strip_prefix('=')?;
+        let rest = rest.trim_start();
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &rest[1..];
+        preceded_by_space.then(|| value.split(quote).next()).flatten()
+    })
+}
@@ -2,84 +2,390 @@
 #[cfg(feature = "serde")]
 extern crate serde;
 
+#[cfg(feature = "serde")]
+mod calc;
+pub mod diagnostics;
+mod edges;
 pub mod error;
+mod grid;
+mod image;
+mod margin;
+mod path;
 pub mod prelude;
 pub mod svg;
+mod text;
 pub mod types;
 
+use std::collections::HashMap;
 use std::path::Path;
 
-use log::debug;
+use image::FlexImage;
+use log::{debug, warn};
 use prelude::*;
-use taffy::prelude::Number as TaffyNumber;
+use taffy::node::MeasureFunc;
+use taffy::prelude::AvailableSpace as TaffyAvailableSpace;
+use taffy::prelude::Rect as TaffyRect;
 use taffy::prelude::Size as TaffySize;
-use taffy::prelude::Style as TaffyStyle;
+use taffy::prelude::TaffyMaxContent;
+use taffy::prelude::{Dimension, LengthPercentage, Position};
 use taffy::Taffy;
+use text::{wrap_width, FontBook};
 
 pub fn compute_svg_string(
     root: FlexNode,
     base_path: Option<&Path>,
 ) -> Result<String, Error> {
-    let layout_root = compute_layout_root(root)?;
+    let layout_root = compute_layout(root, base_path)?;
     crate::svg::compute_svg_string(layout_root, base_path)
 }
 
-fn compute_layout_root(node: FlexNode) -> Result<FlexGeomNode, Error> {
-    let size = TaffySize {
-        height: TaffyNumber::Defined(100.0),
-        width: TaffyNumber::Defined(100.0),
+/// Lays out the document, returning the geometry of every node. Relative
+/// paths, e.g. font and image files, are resolved against `base_path`.
+pub fn compute_layout(
+    node: FlexNode,
+    base_path: Option<&Path>,
+) -> Result<FlexGeomNode, Error> {
+    let root = node.root.clone().unwrap_or_default();
+    let ctx = LayoutContext {
+        base_path,
+        fonts: FontBook::load(&root.fonts, base_path)?,
+        units: FlexUnits {
+            defaults: root.defaults,
+            font_size: root.root_font_size,
+            viewport: root.canvas,
+            ..Default::default()
+        },
+    };
+    let (size, canvas) = match root.canvas_sizing {
+        FlexCanvasSizing::Fixed => (
+            TaffySize {
+                height: TaffyAvailableSpace::Definite(root.canvas.height),
+                width: TaffyAvailableSpace::Definite(root.canvas.width),
+            },
+            Some(root.canvas),
+        ),
+        FlexCanvasSizing::ShrinkToFit => (TaffySize::MAX_CONTENT, None),
     };
     let mut taffy = Taffy::new();
-    let flex_taffy_root = compute_flex_taffy_recursive(node, &mut taffy)?;
+    let flex_taffy_root = compute_flex_taffy_recursive(node, &mut taffy, &ctx)?;
     taffy.compute_layout(flex_taffy_root.taffy_node, size)?;
-    let root = compute_layout_root_recursive(flex_taffy_root, &mut taffy)?;
+    // @NOTE(jshrake): Taffy can't mix units, so calc() expressions are
+    // resolved against the laid out parents until the layout settles
+    let units = FlexUnits { container: canvas, ..ctx.units };
+    let mut is_settled = false;
+    for _ in 0..MAX_CALC_PASSES {
+        if !resolve_calc_recursive(&flex_taffy_root, &mut taffy, &units)? {
+            is_settled = true;
+            break;
+        }
+        taffy.compute_layout(flex_taffy_root.taffy_node, size)?;
+    }
+    if !is_settled {
+        warn!("[calc] layout still changing after {MAX_CALC_PASSES} passes");
+    }
+    let canvas_width = canvas.map(|canvas| canvas.width);
+    let margin =
+        margin::root_margins(&taffy, flex_taffy_root.taffy_node, canvas_width)?;
+    let root = compute_layout_root_recursive(
+        flex_taffy_root,
+        &mut taffy,
+        &ctx.units,
+        FlexPoint::default(),
+        canvas_width,
+        margin,
+    )?;
     Ok(root)
 }
 
+const MAX_CALC_PASSES: usize = 8;
+
+/// Restyles nodes with `calc()` dimensions against the content box of their
+/// laid out parent. Returns whether any style changed.
+fn resolve_calc_recursive(
+    parent: &FlexTaffyNode,
+    taffy: &mut Taffy,
+    units: &FlexUnits,
+) -> Result<bool, Error> {
+    let mut changed = false;
+    if let Some(layout) = &parent.calc_layout {
+        let previous = taffy.style(parent.taffy_node)?;
+        let mut style = layout.clone().to_style(units);
+        style.aspect_ratio = style.aspect_ratio.or(previous.aspect_ratio);
+        if &style != previous {
+            taffy.set_style(parent.taffy_node, style)?;
+            changed = true;
+        }
+    }
+    let size = taffy.layout(parent.taffy_node)?.size;
+    let style = taffy.style(parent.taffy_node)?;
+    let parent_width = units.container.map(|container| container.width);
+    let length = |length| resolve_length_percentage(length, parent_width);
+    let padding = edges(style.padding, length);
+    let border = edges(style.border, length);
+    let units = FlexUnits {
+        container: Some(FlexSize {
+            width: size.width
+                - padding.start
+                - padding.end
+                - border.start
+                - border.end,
+            height: size.height
+                - padding.top
+                - padding.bottom
+                - border.top
+                - border.bottom,
+        }),
+        container_direction: style.flex_direction,
+        ..*units
+    };
+    for child in &parent.nodes {
+        changed |= resolve_calc_recursive(child, taffy, &units)?;
+    }
+    Ok(changed)
+}
+
 fn compute_layout_root_recursive(
     parent: FlexTaffyNode,
     taffy: &mut Taffy,
+    units: &FlexUnits,
+    parent_position: FlexPoint,
+    parent_width: Option<f32>,
+    margin: FlexRect<f32>,
 ) -> Result<FlexGeomNode, Error> {
+    let taffy_layout = *taffy.layout(parent.taffy_node)?;
+    let style = taffy.style(parent.taffy_node)?;
+    let length = |length| resolve_length_percentage(length, parent_width);
+    let padding = edges(style.padding, length);
+    let border = edges(style.border, length);
+    let position =
+        FlexPoint { x: taffy_layout.location.x, y: taffy_layout.location.y };
+    let absolute_position = FlexPoint {
+        x: parent_position.x + position.x,
+        y: parent_position.y + position.y,
+    };
+    let size = taffy_layout.size;
+    // @NOTE(jshrake): Percentage radii resolve against each side of the
+    // box, and can't exceed half of it
+    let radius = |basis: f32| {
+        let radius =
+            parent.decoration.border_radius.map_or(0.0, |radius| match radius
+                .resolve(units, Some(basis))
+            {
+                Dimension::Points(points) => points,
+                Dimension::Percent(percent) => percent * basis,
+                Dimension::Auto => 0.0,
+            });
+        radius.clamp(0.0, 0.5 * basis.max(0.0))
+    };
+    let border_radius =
+        FlexSize { width: radius(size.width), height: radius(size.height) };
+    let content_width = taffy_layout.size.width
+        - padding.start
+        - padding.end
+        - border.start
+        - border.end;
+    let inset = FlexRect {
+        start: padding.start + border.start,
+        end: padding.end + border.end,
+        top: padding.top + border.top,
+        bottom: padding.bottom + border.bottom,
+    };
+    let margins = margin::child_margins(taffy, parent.taffy_node, inset)?;
     let mut children_nodes = vec![];
-    for child in parent.nodes {
-        let layout_node = compute_layout_root_recursive(child, taffy)?;
+    for (child, margin) in parent.nodes.into_iter().zip(margins) {
+        let layout_node = compute_layout_root_recursive(
+            child,
+            taffy,
+            units,
+            absolute_position,
+            Some(content_width),
+            margin,
+        )?;
         children_nodes.push(layout_node);
     }
-    let taffy_layout = taffy.layout(parent.taffy_node)?;
+    let text = parent.text.map(|text| {
+        let size = taffy_layout.size;
+        text.layout(wrap_width(size.width, size.height, &parent.transform))
+    });
 
     Ok(FlexGeomNode {
         element: parent.element,
-        position: FlexPoint {
-            x: taffy_layout.location.x,
-            y: taffy_layout.location.y,
-        },
+        position,
+        absolute_position,
+        padding,
+        border,
+        margin,
+        border_radius,
+        decoration: parent.decoration,
         width: taffy_layout.size.width,
         height: taffy_layout.size.height,
+        text,
+        id: parent.id,
+        transform: parent.transform,
+        z_index: parent.z_index,
+        overflow: parent.overflow,
         root: parent.root,
+        is_label: parent.is_label,
         nodes: children_nodes,
     })
 }
 
+// @NOTE(jshrake): Percentage edges resolve against the width of the
+// parent's content box
+fn resolve_length_percentage(
+    length: LengthPercentage,
+    parent_width: Option<f32>,
+) -> f32 {
+    match length {
+        LengthPercentage::Points(points) => points,
+        LengthPercentage::Percent(percent) => {
+            parent_width.map_or(0.0, |width| width * percent)
+        }
+    }
+}
+
+fn edges<T>(rect: TaffyRect<T>, f: impl Fn(T) -> f32) -> FlexRect<f32> {
+    FlexRect {
+        start: f(rect.left),
+        end: f(rect.right),
+        top: f(rect.top),
+        bottom: f(rect.bottom),
+    }
+}
+
 fn compute_flex_taffy_recursive(
-    parent: FlexNode,
+    mut parent: FlexNode,
     taffy: &mut Taffy,
+    ctx: &LayoutContext,
 ) -> Result<FlexTaffyNode, Error> {
     let child_len = parent.nodes.len();
     let mut flex_taffy_children = Vec::with_capacity(child_len);
     let mut taffy_children = Vec::with_capacity(child_len);
+    grid::place_grid_areas(&parent.layout, &mut parent.nodes);
+    expand_label(&mut parent);
 
-    for child in parent.nodes {
-        let child = compute_flex_taffy_recursive(child, taffy)?;
+    for (i, child) in parent.nodes.into_iter().enumerate() {
+        let mut child = compute_flex_taffy_recursive(child, taffy, ctx)?;
+        // @NOTE(jshrake): The label's text and rect follow the children
+        child.is_label = i >= child_len;
         taffy_children.push(child.taffy_node);
         flex_taffy_children.push(child);
     }
-    let taffy_node =
-        taffy.new_node(TaffyStyle::from(parent.layout), &taffy_children)?;
+    let calc_layout = parent.layout.has_calc().then(|| parent.layout.clone());
+    let mut style = parent.layout.to_style(&ctx.units);
+    let text = parent.element.as_ref().and_then(|el| ctx.fonts.text(el));
+    let image = parent
+        .element
+        .as_ref()
+        .and_then(|el| FlexImage::load(el, ctx.base_path));
+    if let Some(image) = image {
+        style.aspect_ratio = style.aspect_ratio.or(Some(image.aspect_ratio()));
+    }
+    let taffy_node = match (text.clone(), image) {
+        (Some(text), _) if taffy_children.is_empty() => {
+            let measure =
+                move |known, available| text.measure(known, available);
+            let measure = MeasureFunc::Boxed(Box::new(measure));
+            taffy.new_leaf_with_measure(style, measure)?
+        }
+        (_, Some(image)) if taffy_children.is_empty() => {
+            let measure =
+                move |known, available| image.measure(known, available);
+            let measure = MeasureFunc::Boxed(Box::new(measure));
+            taffy.new_leaf_with_measure(style, measure)?
+        }
+        _ => taffy.new_with_children(style, &taffy_children)?,
+    };
     debug!("[taffy-new-node] {:?}, {:?}", &taffy_node, taffy_children);
     Ok(FlexTaffyNode {
         taffy_node,
+        calc_layout,
+        decoration: parent.decoration,
         element: parent.element,
+        text,
+        id: parent.id,
+        transform: parent.transform,
+        z_index: parent.z_index,
+        overflow: parent.overflow,
         root: parent.root,
+        is_label: false,
         nodes: flex_taffy_children,
     })
 }
+
+/// Adds the text and rect of a node's `label` to its children
+fn expand_label(node: &mut FlexNode) {
+    let label = match node.label.take() {
+        Some(label) => label,
+        None => return,
+    };
+    let string = FlexElementAttributeValue::String;
+    let mut rect_attributes = HashMap::new();
+    if let Some(class) = &node.label_class {
+        rect_attributes.insert("class".to_string(), string(class.clone()));
+    }
+    if let Some(style) = &node.label_style {
+        rect_attributes.insert("style".to_string(), string(style.clone()));
+    }
+    // @NOTE(jshrake): Zero insets stretch the label over the node
+    let zero = Some(FlexDimension::Points(0.0));
+    let layout = FlexLayout {
+        position_type: Position::Absolute,
+        position: FlexRect { start: zero, end: zero, top: zero, bottom: zero },
+        size: FlexSize {
+            width: Some(FlexDimension::Auto),
+            height: Some(FlexDimension::Auto),
+        },
+        ..Default::default()
+    };
+    let element = |element: &str, attributes| FlexElement {
+        element: element.to_string(),
+        origin: None,
+        value: None,
+        line_height: None,
+        text_align: None,
+        white_space: Default::default(),
+        points: None,
+        path: None,
+        attributes,
+    };
+    let mut text_attributes = HashMap::new();
+    let text_class = match &node.label_text_class {
+        Some(class) => format!("flexvg-label {class}"),
+        None => "flexvg-label".to_string(),
+    };
+    text_attributes.insert("class".to_string(), string(text_class));
+    text_attributes
+        .insert("dominant-baseline".to_string(), string("middle".to_string()));
+    if let Some(style) = &node.label_text_style {
+        text_attributes.insert("style".to_string(), string(style.clone()));
+    }
+    node.nodes.push(FlexNode {
+        element: Some(FlexElement {
+            origin: Some((0.5, 0.5)),
+            value: Some(label),
+            text_align: Some(FlexTextAlign::Center),
+            ..element("text", text_attributes)
+        }),
+        layout: layout.clone(),
+        transform: FlexTransform {
+            rotate: node.label_rotate,
+            ..Default::default()
+        },
+        z_index: 1,
+        ..Default::default()
+    });
+    node.nodes.push(FlexNode {
+        element: Some(element("rect", rect_attributes)),
+        layout,
+        z_index: -1,
+        ..Default::default()
+    });
+}
+
+/// Document-wide state needed while building the Taffy tree
+struct LayoutContext<'a> {
+    base_path: Option<&'a Path>,
+    fonts: FontBook,
+    units: FlexUnits,
+}
@@ -0,0 +1,672 @@
+use crate::prelude::*;
+use taffy::node::Node as TaffyNode;
+use taffy::prelude::{
+    AlignContent, AlignItems, Dimension, Display, FlexDirection, FlexWrap,
+    LengthPercentage, LengthPercentageAuto, Position, Rect as TaffyRect,
+    Size as TaffySize, Style,
+};
+use taffy::Taffy;
+
+/// Margins of the children of a laid out node, as Taffy used them. `inset`
+/// is the node's padding plus border.
+///
+/// Auto margins are worked out from the free space Taffy gave them: for
+/// absolutely positioned children, the space left in the node's box, and
+/// for flex items, the space left on their flex line. Grid areas aren't
+/// known after layout, so auto margins of grid items are 0, and their
+/// percentages resolve against the node's content box rather than the grid
+/// area. Baseline alignment isn't taken into account when sizing flex lines.
+pub(crate) fn child_margins(
+    taffy: &Taffy,
+    node: TaffyNode,
+    inset: FlexRect<f32>,
+) -> Result<Vec<FlexRect<f32>>, Error> {
+    let style = taffy.style(node)?;
+    let size = taffy.layout(node)?.size;
+    let inner = TaffySize {
+        width: size.width - inset.start - inset.end,
+        height: size.height - inset.top - inset.bottom,
+    };
+    let children = taffy.children(node)?;
+    let mut margins = vec![FlexRect::default(); children.len()];
+    let mut items = vec![];
+    for (i, child) in children.into_iter().enumerate() {
+        let child_style = taffy.style(child)?;
+        if child_style.display == Display::None {
+            continue;
+        }
+        let child_layout = taffy.layout(child)?;
+        let child_size = child_layout.size;
+        // @NOTE(jshrake): Taffy resolves the margins of absolutely
+        // positioned children against the node's border box
+        let is_absolute = child_style.position == Position::Absolute;
+        let basis = if is_absolute { size.width } else { inner.width };
+        let margin = definite_margins(child_style.margin, basis);
+        if is_absolute {
+            let free = TaffySize {
+                width: size.width
+                    - child_size.width
+                    - sum(margin.left, margin.right),
+                height: size.height
+                    - child_size.height
+                    - sum(margin.top, margin.bottom),
+            };
+            // @NOTE(jshrake): Unlike in a flex line, auto margins of
+            // absolutely positioned children don't go negative
+            let (left, right) =
+                fill(margin.left, margin.right, free.width.max(0.0));
+            let (top, bottom) =
+                fill(margin.top, margin.bottom, free.height.max(0.0));
+            margins[i] = FlexRect { start: left, end: right, top, bottom };
+        } else {
+            let location = child_layout.location;
+            let main = match style.flex_direction {
+                FlexDirection::Row | FlexDirection::RowReverse => location.x,
+                FlexDirection::Column | FlexDirection::ColumnReverse => {
+                    location.y
+                }
+            };
+            let is_stretched = is_stretched(style, child_style);
+            items.push(Item {
+                index: i,
+                main,
+                size: child_size,
+                margin,
+                is_stretched,
+            });
+        }
+    }
+    match style.display {
+        Display::Flex => {
+            let gap = match style.flex_direction {
+                FlexDirection::Row | FlexDirection::RowReverse => {
+                    resolve(style.gap.width, inner.width)
+                }
+                FlexDirection::Column | FlexDirection::ColumnReverse => {
+                    resolve(style.gap.height, inner.height)
+                }
+            };
+            let is_wrap = style.flex_wrap != FlexWrap::NoWrap;
+            let lines = flex_lines(items, style.flex_direction, is_wrap);
+            let line_crosses = line_cross_sizes(&lines, style, inner);
+            for (line, line_cross) in lines.iter().zip(line_crosses) {
+                flex_line_margins(
+                    line,
+                    style.flex_direction,
+                    inner,
+                    gap,
+                    line_cross,
+                    &mut margins,
+                );
+            }
+        }
+        _ => {
+            for item in items {
+                let zero = |side: Option<f32>| side.unwrap_or(0.0);
+                margins[item.index] = FlexRect {
+                    start: zero(item.margin.left),
+                    end: zero(item.margin.right),
+                    top: zero(item.margin.top),
+                    bottom: zero(item.margin.bottom),
+                };
+            }
+        }
+    }
+    Ok(margins)
+}
+
+/// Margins of the root node, which has no parent to leave free space in,
+/// with `auto` margins as 0
+pub(crate) fn root_margins(
+    taffy: &Taffy,
+    node: TaffyNode,
+    canvas_width: Option<f32>,
+) -> Result<FlexRect<f32>, Error> {
+    let style = taffy.style(node)?;
+    let margin = definite_margins(style.margin, canvas_width.unwrap_or(0.0));
+    let zero = |side: Option<f32>| side.unwrap_or(0.0);
+    Ok(FlexRect {
+        start: zero(margin.left),
+        end: zero(margin.right),
+        top: zero(margin.top),
+        bottom: zero(margin.bottom),
+    })
+}
+
+/// An in-flow child: its index among the children, where it starts along
+/// the main axis, its size and its margins, with `auto` margins as `None`
+struct Item {
+    index: usize,
+    main: f32,
+    size: TaffySize<f32>,
+    margin: TaffyRect<Option<f32>>,
+    /// Whether `align-self: stretch` sized the child to fill its line
+    is_stretched: bool,
+}
+
+fn is_row(direction: FlexDirection) -> bool {
+    matches!(direction, FlexDirection::Row | FlexDirection::RowReverse)
+}
+
+fn is_stretched(style: &Style, child_style: &Style) -> bool {
+    let align_self = child_style.align_self.or(style.align_items);
+    let margin = child_style.margin;
+    let (size, start, end) = if is_row(style.flex_direction) {
+        (child_style.size.height, margin.top, margin.bottom)
+    } else {
+        (child_style.size.width, margin.left, margin.right)
+    };
+    align_self.unwrap_or(AlignItems::Stretch) == AlignItems::Stretch
+        && size == Dimension::Auto
+        && start != LengthPercentageAuto::Auto
+        && end != LengthPercentageAuto::Auto
+}
+
+fn definite_margins(
+    margin: TaffyRect<LengthPercentageAuto>,
+    basis: f32,
+) -> TaffyRect<Option<f32>> {
+    margin.map(|margin| match margin {
+        LengthPercentageAuto::Points(points) => Some(points),
+        LengthPercentageAuto::Percent(percent) => Some(percent * basis),
+        LengthPercentageAuto::Auto => None,
+    })
+}
+
+fn resolve(length: LengthPercentage, basis: f32) -> f32 {
+    match length {
+        LengthPercentage::Points(points) => points,
+        LengthPercentage::Percent(percent) => percent * basis,
+    }
+}
+
+fn sum(start: Option<f32>, end: Option<f32>) -> f32 {
+    start.unwrap_or(0.0) + end.unwrap_or(0.0)
+}
+
+/// Shares `free` space equally between the auto margins of an axis
+fn fill(start: Option<f32>, end: Option<f32>, free: f32) -> (f32, f32) {
+    match (start, end) {
+        (None, None) => (0.5 * free, 0.5 * free),
+        (None, Some(end)) => (free, end),
+        (Some(start), None) => (start, free),
+        (Some(start), Some(end)) => (start, end),
+    }
+}
+
+/// Splits the in-flow children into flex lines. Items on a line are laid
+/// out in order, so a line ends where the next item starts back along the
+/// main axis.
+fn flex_lines(
+    items: Vec<Item>,
+    direction: FlexDirection,
+    is_wrap: bool,
+) -> Vec<Vec<Item>> {
+    let is_reverse = matches!(
+        direction,
+        FlexDirection::RowReverse | FlexDirection::ColumnReverse
+    );
+    let mut lines: Vec<Vec<Item>> = vec![];
+    for item in items {
+        let wraps = match lines.last().and_then(|line| line.last()) {
+            Some(previous) if is_wrap => {
+                if is_reverse {
+                    item.main > previous.main
+                } else {
+                    item.main < previous.main
+                }
+            }
+            Some(_) => false,
+            None => true,
+        };
+        match lines.last_mut() {
+            Some(line) if !wraps => line.push(item),
+            _ => lines.push(vec![item]),
+        }
+    }
+    lines
+}
+
+/// Cross sizes of the flex lines, as Taffy sizes them. A single line
+/// spans the node, unless `align-content` packs the lines of a wrapping
+/// node. Otherwise lines fit their items, and `align-content: stretch`
+/// grows each by the same amount to fill the node.
+fn line_cross_sizes(
+    lines: &[Vec<Item>],
+    style: &Style,
+    inner: TaffySize<f32>,
+) -> Vec<f32> {
+    let is_row = is_row(style.flex_direction);
+    let (inner_cross, gap) = if is_row {
+        (inner.height, resolve(style.gap.height, inner.height))
+    } else {
+        (inner.width, resolve(style.gap.width, inner.width))
+    };
+    let align_content = style.align_content.unwrap_or(AlignContent::Stretch);
+    let is_wrap = style.flex_wrap != FlexWrap::NoWrap;
+    let fills = matches!(
+        align_content,
+        AlignContent::Stretch
+            | AlignContent::SpaceEvenly
+            | AlignContent::SpaceAround
+    );
+    if lines.len() == 1 && (!is_wrap || fills) {
+        return vec![inner_cross];
+    }
+    let outer_cross = |item: &Item| {
+        let TaffyRect { left, right, top, bottom } = item.margin;
+        if is_row {
+            item.size.height + sum(top, bottom)
+        } else {
+            item.size.width + sum(left, right)
+        }
+    };
+    let fitted: Vec<f32> = lines
+        .iter()
+        .map(|line| line.iter().map(outer_cross).fold(0.0, f32::max))
+        .collect();
+    if align_content != AlignContent::Stretch {
+        return fitted;
+    }
+    // @NOTE(jshrake): Stretched items already fill their grown line, so
+    // only the growth of the other lines is unknown
+    let is_grown = |line: &Vec<Item>| line.iter().any(|item| item.is_stretched);
+    let gaps = gap * lines.len().saturating_sub(1) as f32;
+    let free = inner_cross - gaps - fitted.iter().sum::<f32>();
+    let fitted_count = lines.iter().filter(|line| !is_grown(line)).count();
+    let growth = if free > 0.0 && fitted_count > 0 {
+        free / fitted_count as f32
+    } else {
+        0.0
+    };
+    lines
+        .iter()
+        .zip(fitted)
+        .map(
+            |(line, cross)| if is_grown(line) { cross } else { cross + growth },
+        )
+        .collect()
+}
+
+/// Resolves the margins of the items on one flex line. Auto margins share
+/// the line's free main-axis space equally, and take up what's left of the
+/// line's cross size.
+fn flex_line_margins(
+    line: &[Item],
+    direction: FlexDirection,
+    inner: TaffySize<f32>,
+    gap: f32,
+    line_cross: f32,
+    margins: &mut [FlexRect<f32>],
+) {
+    let is_row = is_row(direction);
+    // @NOTE(jshrake): Main and cross axis views of sizes and margins
+    let main =
+        |size: TaffySize<f32>| if is_row { size.width } else { size.height };
+    let cross =
+        |size: TaffySize<f32>| if is_row { size.height } else { size.width };
+    let main_margins = |margin: &TaffyRect<Option<f32>>| {
+        if is_row {
+            (margin.left, margin.right)
+        } else {
+            (margin.top, margin.bottom)
+        }
+    };
+    let cross_margins = |margin: &TaffyRect<Option<f32>>| {
+        if is_row {
+            (margin.top, margin.bottom)
+        } else {
+            (margin.left, margin.right)
+        }
+    };
+    let outer_main = |item: &Item| {
+        let (start, end) = main_margins(&item.margin);
+        main(item.size) + sum(start, end)
+    };
+    let outer_cross = |item: &Item| {
+        let (start, end) = cross_margins(&item.margin);
+        cross(item.size) + sum(start, end)
+    };
+    let gaps = gap * line.len().saturating_sub(1) as f32;
+    let free_main =
+        main(inner) - line.iter().map(outer_main).sum::<f32>() - gaps;
+    let auto_count = line
+        .iter()
+        .map(|item| {
+            let (start, end) = main_margins(&item.margin);
+            usize::from(start.is_none()) + usize::from(end.is_none())
+        })
+        .sum::<usize>();
+    let auto_main = if free_main > 0.0 && auto_count > 0 {
+        free_main / auto_count as f32
+    } else {
+        0.0
+    };
+    for item in line {
+        let (main_start, main_end) = main_margins(&item.margin);
+        let main_start = main_start.unwrap_or(auto_main);
+        let main_end = main_end.unwrap_or(auto_main);
+        let (cross_start, cross_end) = cross_margins(&item.margin);
+        let (cross_start, cross_end) =
+            fill(cross_start, cross_end, line_cross - outer_cross(item));
+        margins[item.index] = if is_row {
+            FlexRect {
+                start: main_start,
+                end: main_end,
+                top: cross_start,
+                bottom: cross_end,
+            }
+        } else {
+            FlexRect {
+                start: cross_start,
+                end: cross_end,
+                top: main_start,
+                bottom: main_end,
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taffy::prelude::evenly_sized_tracks;
+    use FlexDimension::{Auto, Percent, Points};
+
+    const ZERO: FlexDimension = Points(0.0);
+
+    /// Lays out `parent` and its `children` on a `width` by `height` canvas
+    fn layout(
+        (width, height): (f32, f32),
+        parent: FlexLayout,
+        children: Vec<FlexLayout>,
+    ) -> FlexGeomNode {
+        let root = FlexRootData {
+            canvas: FlexSize { width, height },
+            ..Default::default()
+        };
+        let node = FlexNode {
+            layout: parent,
+            root: Some(root),
+            nodes: children
+                .into_iter()
+                .map(|layout| FlexNode { layout, ..Default::default() })
+                .collect(),
+            ..Default::default()
+        };
+        crate::compute_layout(node, None).unwrap()
+    }
+
+    /// A `width` by `height` child with `[left, top, right, bottom]` margins
+    fn child(
+        width: f32,
+        height: f32,
+        margin: [FlexDimension; 4],
+    ) -> FlexLayout {
+        let [start, top, end, bottom] = margin.map(Some);
+        FlexLayout {
+            size: FlexSize {
+                width: Some(Points(width)),
+                height: Some(Points(height)),
+            },
+            margin: FlexRect { start, end, top, bottom },
+            ..Default::default()
+        }
+    }
+
+    fn edges(length: FlexDimension) -> FlexRect<Option<FlexDimension>> {
+        let length = Some(length);
+        FlexRect { start: length, end: length, top: length, bottom: length }
+    }
+
+    fn absolute(
+        position: FlexRect<Option<FlexDimension>>,
+        layout: FlexLayout,
+    ) -> FlexLayout {
+        FlexLayout { position_type: Position::Absolute, position, ..layout }
+    }
+
+    fn gap(length: f32) -> Option<FlexGap> {
+        Some(FlexGap { row: Points(length), column: Points(length) })
+    }
+
+    /// Margin box of each child as `[left, top, right, bottom]`, from where
+    /// Taffy placed the child and the margins reported for it
+    fn margin_boxes(node: &FlexGeomNode) -> Vec<[f32; 4]> {
+        let margin_box = |child: &FlexGeomNode| {
+            let FlexPoint { x, y } = child.position;
+            let margin = child.margin;
+            [
+                x - margin.start,
+                y - margin.top,
+                x + child.width + margin.end,
+                y + child.height + margin.bottom,
+            ]
+        };
+        node.nodes.iter().map(margin_box).collect()
+    }
+
+    #[test]
+    fn fills_rows_and_columns() {
+        let root = layout(
+            (200.0, 100.0),
+            FlexLayout::default(),
+            vec![
+                child(20.0, 20.0, [Auto, ZERO, ZERO, ZERO]),
+                child(20.0, 20.0, [ZERO, Auto, ZERO, Auto]),
+                child(20.0, 20.0, [ZERO, Auto, Auto, ZERO]),
+            ],
+        );
+        let expected = vec![
+            [0.0, 0.0, 90.0, 20.0],
+            [90.0, 0.0, 110.0, 100.0],
+            [110.0, 0.0, 200.0, 100.0],
+        ];
+        assert_eq!(margin_boxes(&root), expected);
+        assert_eq!(root.nodes[1].margin.top, 40.0);
+        let column = FlexLayout {
+            flex_direction: FlexDirection::Column,
+            ..Default::default()
+        };
+        let root = layout(
+            (100.0, 200.0),
+            column,
+            vec![
+                child(20.0, 20.0, [ZERO, Auto, ZERO, ZERO]),
+                child(20.0, 20.0, [Auto, ZERO, Auto, ZERO]),
+                child(20.0, 20.0, [Auto, ZERO, ZERO, Auto]),
+            ],
+        );
+        let expected = vec![
+            [0.0, 0.0, 20.0, 90.0],
+            [0.0, 90.0, 100.0, 110.0],
+            [0.0, 110.0, 100.0, 200.0],
+        ];
+        assert_eq!(margin_boxes(&root), expected);
+    }
+
+    #[test]
+    fn fills_reversed_lines() {
+        let reversed = |flex_direction| FlexLayout {
+            flex_direction,
+            ..Default::default()
+        };
+        let root = layout(
+            (200.0, 100.0),
+            reversed(FlexDirection::RowReverse),
+            vec![
+                child(20.0, 20.0, [Auto, ZERO, ZERO, ZERO]),
+                child(20.0, 20.0, [ZERO; 4]),
+            ],
+        );
+        let expected = vec![[20.0, 0.0, 200.0, 20.0], [0.0, 0.0, 20.0, 20.0]];
+        assert_eq!(margin_boxes(&root), expected);
+        let root = layout(
+            (100.0, 200.0),
+            reversed(FlexDirection::ColumnReverse),
+            vec![
+                child(20.0, 20.0, [ZERO, ZERO, ZERO, Auto]),
+                child(20.0, 20.0, [ZERO; 4]),
+            ],
+        );
+        let expected = vec![[0.0, 20.0, 20.0, 200.0], [0.0, 0.0, 20.0, 20.0]];
+        assert_eq!(margin_boxes(&root), expected);
+    }
+
+    #[test]
+    fn fills_wrapped_lines() {
+        let wrap = |align_content| FlexLayout {
+            flex_wrap: FlexWrap::Wrap,
+            align_content,
+            ..Default::default()
+        };
+        // @NOTE(jshrake): A lone line of a wrapping node is stretched over
+        // the node by align-content
+        let root = layout(
+            (200.0, 100.0),
+            wrap(None),
+            vec![child(20.0, 20.0, [ZERO, Auto, ZERO, Auto])],
+        );
+        assert_eq!(margin_boxes(&root), vec![[0.0, 0.0, 20.0, 100.0]]);
+        let wrapped = |align_content| {
+            let auto_height = FlexLayout {
+                size: FlexSize {
+                    width: Some(Points(80.0)),
+                    height: Some(Auto),
+                },
+                ..Default::default()
+            };
+            layout(
+                (200.0, 100.0),
+                wrap(Some(align_content)),
+                vec![
+                    child(80.0, 20.0, [ZERO, Auto, ZERO, Auto]),
+                    auto_height,
+                    child(80.0, 20.0, [ZERO, Auto, ZERO, ZERO]),
+                ],
+            )
+        };
+        let expected = vec![
+            [0.0, 0.0, 80.0, 50.0],
+            [80.0, 0.0, 160.0, 50.0],
+            [0.0, 50.0, 80.0, 100.0],
+        ];
+        assert_eq!(margin_boxes(&wrapped(AlignContent::Stretch)), expected);
+        let expected = vec![
+            [0.0, 0.0, 80.0, 20.0],
+            [80.0, 0.0, 160.0, 20.0],
+            [0.0, 20.0, 80.0, 40.0],
+        ];
+        assert_eq!(margin_boxes(&wrapped(AlignContent::FlexStart)), expected);
+        let root = wrapped(AlignContent::Center);
+        assert_eq!(root.nodes[2].margin.top, 0.0);
+        assert_eq!(root.nodes[2].position.y, 50.0);
+    }
+
+    #[test]
+    fn leaves_out_gaps() {
+        // @NOTE(jshrake): Taffy takes the gaps out of the free space, but
+        // doesn't add them between items with auto margins
+        let root = layout(
+            (200.0, 100.0),
+            FlexLayout { gap: gap(10.0), ..Default::default() },
+            vec![
+                child(20.0, 20.0, [ZERO, ZERO, Auto, ZERO]),
+                child(20.0, 20.0, [ZERO; 4]),
+                child(20.0, 20.0, [Auto, ZERO, ZERO, ZERO]),
+            ],
+        );
+        let expected = vec![
+            [0.0, 0.0, 80.0, 20.0],
+            [80.0, 0.0, 100.0, 20.0],
+            [100.0, 0.0, 180.0, 20.0],
+        ];
+        assert_eq!(margin_boxes(&root), expected);
+        let wrap = FlexLayout {
+            flex_wrap: FlexWrap::Wrap,
+            gap: gap(20.0),
+            ..Default::default()
+        };
+        let root = layout(
+            (200.0, 100.0),
+            wrap,
+            vec![
+                child(80.0, 20.0, [ZERO, Auto, ZERO, Auto]),
+                child(80.0, 20.0, [ZERO; 4]),
+                child(80.0, 20.0, [ZERO, Auto, ZERO, ZERO]),
+            ],
+        );
+        let expected = vec![
+            [0.0, 0.0, 80.0, 40.0],
+            [100.0, 0.0, 180.0, 20.0],
+            [0.0, 60.0, 80.0, 100.0],
+        ];
+        assert_eq!(margin_boxes(&root), expected);
+    }
+
+    #[test]
+    fn fills_absolute_children() {
+        let root = layout(
+            (200.0, 100.0),
+            FlexLayout::default(),
+            vec![
+                absolute(edges(ZERO), child(50.0, 20.0, [Auto; 4])),
+                absolute(
+                    edges(ZERO),
+                    child(50.0, 20.0, [Auto, Points(10.0), ZERO, ZERO]),
+                ),
+            ],
+        );
+        let expected = vec![[0.0, 0.0, 200.0, 100.0], [0.0, 0.0, 200.0, 30.0]];
+        assert_eq!(margin_boxes(&root), expected);
+        assert_eq!(root.nodes[0].position, FlexPoint { x: 75.0, y: 40.0 });
+    }
+
+    #[test]
+    fn resolves_percentages() {
+        // @NOTE(jshrake): In-flow children resolve against the content box,
+        // absolutely positioned ones against the border box
+        let top_left =
+            FlexRect { start: Some(ZERO), top: Some(ZERO), ..edges(Auto) };
+        let root = layout(
+            (200.0, 100.0),
+            FlexLayout { padding: edges(Points(10.0)), ..Default::default() },
+            vec![
+                child(20.0, 20.0, [Percent(0.1), Percent(0.05), ZERO, ZERO]),
+                absolute(
+                    top_left,
+                    child(20.0, 20.0, [Percent(0.1), ZERO, ZERO, ZERO]),
+                ),
+            ],
+        );
+        let margin = root.nodes[0].margin;
+        assert_eq!((margin.start, margin.top), (18.0, 9.0));
+        assert_eq!(root.nodes[0].position, FlexPoint { x: 28.0, y: 19.0 });
+        assert_eq!(root.nodes[1].margin.start, 20.0);
+        assert_eq!(root.nodes[1].position.x, 20.0);
+    }
+
+    #[test]
+    fn keeps_definite_grid_margins() {
+        let grid = FlexLayout {
+            display: Display::Grid,
+            grid_template_columns: evenly_sized_tracks(2),
+            ..Default::default()
+        };
+        let root = layout(
+            (200.0, 100.0),
+            grid,
+            vec![
+                child(20.0, 20.0, [Points(5.0), Points(10.0), ZERO, ZERO]),
+                child(20.0, 20.0, [Auto, ZERO, ZERO, ZERO]),
+            ],
+        );
+        let margin = root.nodes[0].margin;
+        assert_eq!((margin.start, margin.top), (5.0, 10.0));
+        assert_eq!(root.nodes[0].position, FlexPoint { x: 5.0, y: 10.0 });
+        // @NOTE(jshrake): Taffy pushes the item to the end of its grid
+        // area, which isn't known after layout
+        assert_eq!(root.nodes[1].position.x, 180.0);
+        assert_eq!(root.nodes[1].margin.start, 0.0);
+    }
+}
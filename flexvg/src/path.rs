@@ -0,0 +1,161 @@
+use std::fmt::Write;
+
+/// Scales SVG path data given in 0..1 box coordinates, e.g. `M0 0 L1 0.5
+/// L0 1 Z`, to the box at `left, top` with size `width, height`. Relative
+/// commands are scaled without being moved.
+pub(crate) fn scale_path(
+    d: &str,
+    left: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+) -> Result<String, String> {
+    let mut cursor = Cursor { rest: d };
+    let mut scaled = String::with_capacity(d.len());
+    let mut command: Option<char> = None;
+    while !cursor.is_empty() {
+        let c = match (cursor.command(), command) {
+            (Some(c), _) => {
+                write!(scaled, "{c}").unwrap();
+                c
+            }
+            // @NOTE(jshrake): Numbers after the parameters of a command
+            // repeat the command
+            (None, Some(c)) if !c.eq_ignore_ascii_case(&'z') => {
+                write!(scaled, " ").unwrap();
+                c
+            }
+            (None, _) => return Err(format!("expected a command in `{d}`")),
+        };
+        let (dx, dy) =
+            if c.is_ascii_lowercase() { (0.0, 0.0) } else { (left, top) };
+        let params: &[Param] = match c.to_ascii_lowercase() {
+            'z' => &[],
+            'm' | 'l' | 't' => &[Param::X, Param::Y],
+            'h' => &[Param::X],
+            'v' => &[Param::Y],
+            'c' => {
+                &[Param::X, Param::Y, Param::X, Param::Y, Param::X, Param::Y]
+            }
+            's' | 'q' => &[Param::X, Param::Y, Param::X, Param::Y],
+            'a' => &[
+                Param::Width,
+                Param::Height,
+                Param::Angle,
+                Param::Flag,
+                Param::Flag,
+                Param::X,
+                Param::Y,
+            ],
+            _ => return Err(format!("unknown command `{c}` in `{d}`")),
+        };
+        for (i, param) in params.iter().enumerate() {
+            let value = cursor
+                .number(*param == Param::Flag)
+                .ok_or_else(|| format!("missing `{c}` parameter in `{d}`"))?;
+            let value = match param {
+                Param::X => dx + value * width,
+                Param::Y => dy + value * height,
+                Param::Width => value * width,
+                Param::Height => value * height,
+                Param::Angle | Param::Flag => value,
+            };
+            let separator = if i == 0 { "" } else { " " };
+            write!(scaled, "{separator}{value}").unwrap();
+        }
+        // @NOTE(jshrake): Points after a moveto are lines
+        command = match c {
+            'M' => Some('L'),
+            'm' => Some('l'),
+            c => Some(c),
+        };
+    }
+    Ok(scaled)
+}
+
+/// Scales `points` in 0..1 box coordinates into a `points` attribute
+pub(crate) fn scale_points(
+    points: &[(f32, f32)],
+    left: f32,
+    top: f32,
+    width: f32,
+    height: f32,
+) -> String {
+    let points = points
+        .iter()
+        .map(|(x, y)| format!("{},{}", left + x * width, top + y * height));
+    points.collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Param {
+    X,
+    Y,
+    Width,
+    Height,
+    Angle,
+    Flag,
+}
+
+struct Cursor<'a> {
+    rest: &'a str,
+}
+
+impl Cursor<'_> {
+    fn skip_separators(&mut self) {
+        self.rest = self
+            .rest
+            .trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+
+    fn is_empty(&mut self) -> bool {
+        self.skip_separators();
+        self.rest.is_empty()
+    }
+
+    /// Reads a command letter, if the next token is one
+    fn command(&mut self) -> Option<char> {
+        self.skip_separators();
+        let c = self.rest.chars().next().filter(char::is_ascii_alphabetic)?;
+        self.rest = &self.rest[1..];
+        Some(c)
+    }
+
+    /// Reads a number, or a single `0` or `1` for arc flags, which may be
+    /// written without separators
+    fn number(&mut self, flag: bool) -> Option<f32> {
+        self.skip_separators();
+        let len = if flag {
+            usize::from(self.rest.starts_with(['0', '1']))
+        } else {
+            number_len(self.rest)
+        };
+        let number = self.rest[..len].parse().ok()?;
+        self.rest = &self.rest[len..];
+        Some(number)
+    }
+}
+
+/// Length of the number at the start of `s`, e.g. 2 for `-.5.5`
+fn number_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let digits = |mut i: usize| {
+        while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+            i += 1;
+        }
+        i
+    };
+    let mut i = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+    i = digits(i);
+    if bytes.get(i) == Some(&b'.') {
+        i = digits(i + 1);
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        let sign = usize::from(matches!(bytes.get(i + 1), Some(b'+' | b'-')));
+        let exponent = digits(i + 1 + sign);
+        if exponent > i + 1 + sign {
+            i = exponent;
+        }
+    }
+    i
+}
@@ -1,36 +1,75 @@
+use crate::diagnostics::NodePath;
+use crate::path::{scale_path, scale_points};
 use crate::prelude::*;
 use std::fmt::Write;
 use std::{fs::File, io::Read, path::Path};
 
-pub(crate) fn compute_svg_string(
+/// Renders a laid out document, see [`crate::compute_layout`].
+pub fn compute_svg_string(
     node: FlexGeomNode,
     base_path: Option<&Path>,
 ) -> Result<String, Error> {
     let mut svg = String::with_capacity(8096);
+    let root = node.root.clone().unwrap_or_default();
+    let edges = crate::edges::compute_edges_string(&node, &root)?;
+    let canvas = match root.canvas_sizing {
+        FlexCanvasSizing::Fixed => root.canvas,
+        FlexCanvasSizing::ShrinkToFit => FlexSize {
+            width: node.position.x + node.width,
+            height: node.position.y + node.height,
+        },
+    };
+    let (min_x, min_y, view_width, view_height) =
+        root.view_box.unwrap_or((0.0, 0.0, canvas.width, canvas.height));
+    write!(
+        svg,
+        r#"<svg version="1.0" width="{}" height="{}" viewBox="{min_x} {min_y} {view_width} {view_height}" "#,
+        canvas.width, canvas.height
+    ).unwrap();
+    if let Some(preserve_aspect_ratio) = &root.preserve_aspect_ratio {
+        write!(svg, r#"preserveAspectRatio="{preserve_aspect_ratio}" "#)
+            .unwrap();
+    }
     writeln!(
         svg,
-        r#"<svg version="1.0" width="{}" height="{}" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">"#,
-        node.width, node.height
+        r#"xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">"#
     ).unwrap();
-    if let Some(root) = &node.root {
-        if let Some(stylesheet) = &root.stylesheet {
-            let stylesheet_path = if let Some(base_path) = base_path {
-                base_path.join(stylesheet)
-            } else {
-                Path::new(stylesheet).to_path_buf()
-            };
-            writeln!(svg, "<style>").unwrap();
-            if stylesheet_path.exists() {
-                let mut contents = String::new();
-                File::open(stylesheet_path)?.read_to_string(&mut contents)?;
-                writeln!(svg, "{contents}").unwrap();
-            } else {
-                writeln!(svg, "{stylesheet}").unwrap();
-            };
-            writeln!(svg, "</style>").unwrap();
-        }
+    if let Some(stylesheet) = &root.stylesheet {
+        let stylesheet_path = if let Some(base_path) = base_path {
+            base_path.join(stylesheet)
+        } else {
+            Path::new(stylesheet).to_path_buf()
+        };
+        writeln!(svg, "<style>").unwrap();
+        if stylesheet_path.exists() {
+            let mut contents = String::new();
+            File::open(stylesheet_path)?.read_to_string(&mut contents)?;
+            writeln!(svg, "{contents}").unwrap();
+        } else {
+            writeln!(svg, "{stylesheet}").unwrap();
+        };
+        writeln!(svg, "</style>").unwrap();
+    }
+    // @NOTE(jshrake): The overlay is collected separately so that it draws
+    // on top of every element
+    let mut overlay = root.debug.then(|| DebugOverlay {
+        svg: String::new(),
+        font_size: view_width.min(view_height) / 50.0,
+    });
+    compute_svg_string_recursive(
+        node,
+        &mut svg,
+        root.paint_order,
+        overlay.as_mut(),
+        &mut vec![],
+    )?;
+    svg.push_str(&edges);
+    if let Some(overlay) = overlay {
+        writeln!(svg, r#"<g class="flexvg-debug" pointer-events="none">"#)
+            .unwrap();
+        svg.push_str(&overlay.svg);
+        writeln!(svg, "</g>").unwrap();
     }
-    compute_svg_string_recursive(node, &mut svg, 0.0, 0.0)?;
     writeln!(svg, r#"</svg>"#).unwrap();
     Ok(svg)
 }
@@ -38,47 +77,399 @@ pub(crate) fn compute_svg_string(
 fn compute_svg_string_recursive(
     node: FlexGeomNode,
     svg: &mut String,
-    x: f32,
-    y: f32,
+    paint_order: FlexPaintOrder,
+    mut overlay: Option<&mut DebugOverlay>,
+    path: &mut Vec<usize>,
 ) -> Result<(), Error> {
+    // @NOTE(jshrake): A label covers its node, which is already drawn
+    if let Some(overlay) = overlay.as_deref_mut().filter(|_| !node.is_label) {
+        overlay.draw(&node, path);
+    }
     let has_elements = node.nodes.iter().any(|n| n.element.is_some());
-    if has_elements {
-        writeln!(svg, "<g>").unwrap();
+    let mut group_attributes = String::new();
+    if let Some(id) = &node.id {
+        write!(group_attributes, r#" id="{id}""#).unwrap();
     }
-    if let Some(el) = node.element {
-        let mut x = x + node.position.x;
-        let mut y = y + node.position.y;
-        if let Some(origin) = el.origin {
-            x += origin.0 * node.width;
-            y += origin.1 * node.height;
+    if let Some(transform) = svg_transform(&node) {
+        write!(group_attributes, r#" transform="{transform}""#).unwrap();
+    }
+    if let Some(opacity) = node.decoration.opacity {
+        write!(group_attributes, r#" opacity="{opacity}""#).unwrap();
+    }
+    let clip_id = (node.overflow == FlexOverflow::Hidden)
+        .then(|| format!("flexvg-clip{}", clip_id_suffix(path)));
+    if let Some(clip_id) = &clip_id {
+        write!(group_attributes, r#" clip-path="url(#{clip_id})""#).unwrap();
+    }
+    let has_decoration = node.decoration.background.is_some()
+        || node.decoration.border().is_some();
+    // @NOTE(jshrake): Transforms and clips apply to the children and the
+    // decoration too, so they go on a group unless the node is a lone
+    // element
+    let has_group = has_elements
+        || (!group_attributes.is_empty()
+            && (!node.nodes.is_empty() || has_decoration));
+    if let Some(clip_id) = clip_id {
+        if has_group || node.element.is_some() {
+            let FlexPoint { x, y } = node.absolute_position;
+            let (w, h) = (node.width, node.height);
+            let FlexSize { width: rx, height: ry } = node.border_radius;
+            writeln!(
+                svg,
+                r#"<clipPath id="{clip_id}"><rect x="{x}" y="{y}" width="{w}" height="{h}" rx="{rx}" ry="{ry}" /></clipPath>"#
+            )
+            .unwrap();
         }
+    }
+    if has_group {
+        writeln!(svg, "<g{group_attributes}>").unwrap();
+    }
+    write_background(&node, svg);
+    write_border(&node, svg);
+    if let Some(el) = node.element {
+        let left = node.absolute_position.x;
+        let top = node.absolute_position.y;
         let w = node.width;
         let h = node.height;
+        let (origin_x, origin_y) = el.origin.unwrap_or((0.0, 0.0));
+        let x = match el.text_align {
+            Some(FlexTextAlign::Start) => left,
+            Some(FlexTextAlign::Center) => left + 0.5 * w,
+            Some(FlexTextAlign::End) => left + w,
+            None => left + origin_x * w,
+        };
+        let y = top + origin_y * h;
         let element = el.element;
         let value = el.value.as_deref().unwrap_or("");
-        write!(svg, r#"<{element} x="{x}" y="{y}"  width="{w}" height="{h}" "#)
-            .unwrap();
+        // @NOTE(jshrake): Shapes without a box of their own are inscribed
+        // in the laid out box
+        let (cx, cy) = (left + 0.5 * w, top + 0.5 * h);
+        match element.as_str() {
+            "circle" => {
+                let r = 0.5 * w.min(h);
+                write!(svg, r#"<{element} cx="{cx}" cy="{cy}" r="{r}" "#)
+            }
+            "ellipse" => {
+                let (rx, ry) = (0.5 * w, 0.5 * h);
+                write!(
+                    svg,
+                    r#"<{element} cx="{cx}" cy="{cy}" rx="{rx}" ry="{ry}" "#
+                )
+            }
+            "polyline" | "polygon" if el.points.is_some() => {
+                let points = match &el.points {
+                    Some(FlexPoints::Box(points)) => {
+                        scale_points(points, left, top, w, h)
+                    }
+                    Some(FlexPoints::Svg(points)) => points.clone(),
+                    None => String::new(),
+                };
+                write!(svg, r#"<{element} points="{points}" "#)
+            }
+            "path" if el.path.is_some() => {
+                let d = el.path.as_deref().unwrap_or_default();
+                let d = scale_path(d, left, top, w, h).map_err(Error::Path)?;
+                write!(svg, r#"<{element} d="{d}" "#)
+            }
+            // @NOTE(jshrake): Lines run corner to corner, so a box with no
+            // height draws a horizontal line
+            "line" => {
+                let (right, bottom) = (left + w, top + h);
+                write!(
+                    svg,
+                    r#"<{element} x1="{left}" y1="{top}" x2="{right}" y2="{bottom}" "#
+                )
+            }
+            _ => write!(
+                svg,
+                r#"<{element} x="{x}" y="{y}"  width="{w}" height="{h}" "#
+            ),
+        }
+        .unwrap();
+        if let Some(text_align) = el.text_align {
+            let anchor = match text_align {
+                FlexTextAlign::Start => "start",
+                FlexTextAlign::Center => "middle",
+                FlexTextAlign::End => "end",
+            };
+            write!(svg, r#"text-anchor="{anchor}" "#).unwrap();
+        }
+        if !has_group && !group_attributes.is_empty() {
+            write!(svg, "{} ", group_attributes.trim_start()).unwrap();
+        }
         for (attrib_name, attrib_value) in el.attributes.iter() {
             write!(svg, r#"{attrib_name}="{attrib_value}" "#).unwrap();
         }
         write!(svg, r#">"#).unwrap();
-        write!(svg, r#"{value}"#).unwrap();
+        match node.text {
+            Some(text) if text.lines.len() > 1 => {
+                // @NOTE(jshrake): Lines are stacked around the origin, so an
+                // origin of 0.5 keeps the block vertically centered
+                let line_count = text.lines.len() as f32;
+                let first_dy =
+                    -(line_count - 1.0) * text.line_height * origin_y;
+                for (i, line) in text.lines.iter().enumerate() {
+                    let dy = if i == 0 { first_dy } else { text.line_height };
+                    write!(svg, r#"<tspan x="{x}" dy="{dy}">{line}</tspan>"#)
+                        .unwrap();
+                }
+            }
+            _ => write!(svg, r#"{value}"#).unwrap(),
+        }
         writeln!(svg, r#"</{element}>"#).unwrap();
     }
-    let mut children = node.nodes;
+    let mut children: Vec<_> = node.nodes.into_iter().enumerate().collect();
     // @NOTE(jshrake): Reverse the children so that the
     // first nodes specified in a list draw on top
-    children.reverse();
-    for child in children {
+    if paint_order == FlexPaintOrder::FirstOnTop {
+        children.reverse();
+    }
+    // @NOTE(jshrake): The sort is stable, so siblings with the same z-index
+    // keep the paint order
+    children.sort_by_key(|(_, child)| child.z_index);
+    for (i, child) in children {
+        path.push(i);
         compute_svg_string_recursive(
             child,
             svg,
-            x + node.position.x,
-            y + node.position.y,
+            paint_order,
+            overlay.as_deref_mut(),
+            path,
         )?;
+        path.pop();
     }
-    if has_elements {
+    if has_group {
         writeln!(svg, "</g>").unwrap();
     }
     Ok(())
 }
+
+/// Fills the border box of the node, behind its border, element and
+/// children
+fn write_background(node: &FlexGeomNode, svg: &mut String) {
+    let background = match &node.decoration.background {
+        Some(background) => background,
+        None => return,
+    };
+    let FlexPoint { x, y } = node.absolute_position;
+    let (w, h) = (node.width, node.height);
+    let FlexSize { width: rx, height: ry } = node.border_radius;
+    match node.decoration.background_image() {
+        // @NOTE(jshrake): Like `background-size: cover`, the image fills
+        // the box and is cropped to it. Rounded corners aren't applied.
+        Some(href) => writeln!(
+            svg,
+            r#"<image x="{x}" y="{y}" width="{w}" height="{h}" href="{href}" preserveAspectRatio="xMidYMid slice" />"#
+        ),
+        None => writeln!(
+            svg,
+            r#"<rect x="{x}" y="{y}" width="{w}" height="{h}" rx="{rx}" ry="{ry}" fill="{background}" />"#
+        ),
+    }
+    .unwrap();
+}
+
+/// Draws the border of the node on its border box: a stroked rect when the
+/// widths are equal, and a filled ring otherwise
+fn write_border(node: &FlexGeomNode, svg: &mut String) {
+    let style = match node.decoration.border() {
+        Some(style) => style,
+        None => return,
+    };
+    let border = node.border;
+    let widths = [border.start, border.end, border.top, border.bottom];
+    if widths.iter().all(|width| *width <= 0.0) {
+        return;
+    }
+    let color = node.decoration.border_color.as_deref().unwrap_or("black");
+    let FlexPoint { x, y } = node.absolute_position;
+    let (w, h) = (node.width, node.height);
+    let FlexSize { width: rx, height: ry } = node.border_radius;
+    if widths.iter().all(|width| *width == border.start) {
+        // @NOTE(jshrake): Strokes are centered on the path, so the rect is
+        // inset by half the border width
+        let width = border.start;
+        let half = 0.5 * width;
+        let dash = match style {
+            FlexBorderStyle::Dashed => {
+                format!(
+                    r#" stroke-dasharray="{} {}""#,
+                    3.0 * width,
+                    2.0 * width
+                )
+            }
+            FlexBorderStyle::Dotted => format!(
+                r#" stroke-dasharray="0 {}" stroke-linecap="round""#,
+                2.0 * width
+            ),
+            FlexBorderStyle::None | FlexBorderStyle::Solid => String::new(),
+        };
+        writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" ry="{}" fill="none" stroke="{color}" stroke-width="{width}"{dash} />"#,
+            x + half,
+            y + half,
+            (w - width).max(0.0),
+            (h - width).max(0.0),
+            (rx - half).max(0.0),
+            (ry - half).max(0.0),
+        )
+        .unwrap();
+    } else {
+        let outer = rounded_rect(x, y, w, h, [(rx, ry); 4]);
+        let inner_radius = |horizontal: f32, vertical: f32| {
+            ((rx - horizontal).max(0.0), (ry - vertical).max(0.0))
+        };
+        let inner = rounded_rect(
+            x + border.start,
+            y + border.top,
+            (w - border.start - border.end).max(0.0),
+            (h - border.top - border.bottom).max(0.0),
+            [
+                inner_radius(border.start, border.top),
+                inner_radius(border.end, border.top),
+                inner_radius(border.end, border.bottom),
+                inner_radius(border.start, border.bottom),
+            ],
+        );
+        writeln!(
+            svg,
+            r#"<path d="{outer}{inner}" fill="{color}" fill-rule="evenodd" />"#
+        )
+        .unwrap();
+    }
+}
+
+/// Path of a box with elliptical corners, given as `(rx, ry)` clockwise
+/// from the top left
+fn rounded_rect(
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+    radii: [(f32, f32); 4],
+) -> String {
+    let [(rx0, ry0), (rx1, ry1), (rx2, ry2), (rx3, ry3)] = radii;
+    let (right, bottom) = (x + w, y + h);
+    format!(
+        "M{} {y}H{}A{rx1} {ry1} 0 0 1 {right} {}V{}A{rx2} {ry2} 0 0 1 {} {bottom}H{}A{rx3} {ry3} 0 0 1 {x} {}V{}A{rx0} {ry0} 0 0 1 {} {y}Z",
+        x + rx0,
+        right - rx1,
+        y + ry1,
+        bottom - ry2,
+        right - rx2,
+        x + rx3,
+        bottom - ry3,
+        y + ry0,
+        x + rx0,
+    )
+}
+
+/// Unique per node, e.g. `-1-0` for `nodes[1].nodes[0]`
+fn clip_id_suffix(path: &[usize]) -> String {
+    path.iter().map(|i| format!("-{i}")).collect()
+}
+
+/// The node's transforms as an SVG transform list, applied around its
+/// `transform-origin` in the CSS order: `rotate`, `scale`, then `transform`
+fn svg_transform(node: &FlexGeomNode) -> Option<String> {
+    let transform = &node.transform;
+    if transform.is_identity() {
+        return None;
+    }
+    let (origin_x, origin_y) = transform.transform_origin.unwrap_or((0.5, 0.5));
+    let x = node.absolute_position.x + origin_x * node.width;
+    let y = node.absolute_position.y + origin_y * node.height;
+    let mut svg = format!("translate({x} {y})");
+    if let Some(rotate) = transform.rotate {
+        write!(svg, " rotate({rotate})").unwrap();
+    }
+    if let Some((scale_x, scale_y)) = transform.scale {
+        write!(svg, " scale({scale_x} {scale_y})").unwrap();
+    }
+    if let Some(transform) = &transform.transform {
+        write!(svg, " {transform}").unwrap();
+    }
+    write!(svg, " translate({} {})", 0.0 - x, 0.0 - y).unwrap();
+    Some(svg)
+}
+
+/// Devtools-style boxes drawn over every node, with or without an element
+struct DebugOverlay {
+    svg: String,
+    font_size: f32,
+}
+
+impl DebugOverlay {
+    const MARGIN: &'static str = "#f9cc9d";
+    const BORDER: &'static str = "#fddd9b";
+    const PADDING: &'static str = "#c3d08b";
+    const CONTENT: &'static str = "#8cb6c0";
+
+    fn draw(&mut self, node: &FlexGeomNode, path: &[usize]) {
+        let (x, y) = (node.absolute_position.x, node.absolute_position.y);
+        let (m, b) = (&node.margin, &node.border);
+        let border_box = [x, y, node.width, node.height];
+        let margin_box = [
+            x - m.start,
+            y - m.top,
+            node.width + m.start + m.end,
+            node.height + m.top + m.bottom,
+        ];
+        let padding_box = [
+            x + b.start,
+            y + b.top,
+            node.width - b.start - b.end,
+            node.height - b.top - b.bottom,
+        ];
+        let content = node.content_position();
+        let content_size = node.content_size();
+        let content_box =
+            [content.x, content.y, content_size.width, content_size.height];
+        self.ring(margin_box, border_box, Self::MARGIN);
+        self.ring(border_box, padding_box, Self::BORDER);
+        self.ring(padding_box, content_box, Self::PADDING);
+        let [x, y, w, h] = content_box;
+        writeln!(
+            self.svg,
+            r#"<rect x="{x}" y="{y}" width="{}" height="{}" fill="{}" fill-opacity="0.3" />"#,
+            w.max(0.0),
+            h.max(0.0),
+            Self::CONTENT
+        )
+        .unwrap();
+        let [x, y, w, h] = border_box;
+        let font_size = self.font_size;
+        writeln!(
+            self.svg,
+            r#"<rect x="{x}" y="{y}" width="{}" height="{}" fill="none" stroke="black" stroke-opacity="0.5" stroke-width="{}" />"#,
+            w.max(0.0),
+            h.max(0.0),
+            font_size / 10.0
+        )
+        .unwrap();
+        writeln!(
+            self.svg,
+            r#"<text x="{x}" y="{y}" dy="1em" font-size="{font_size}" font-family="monospace">{} {w}x{h}</text>"#,
+            NodePath(path)
+        )
+        .unwrap();
+    }
+
+    /// Fills the area between two boxes, each `[x, y, width, height]`
+    fn ring(&mut self, outer: [f32; 4], inner: [f32; 4], fill: &str) {
+        if outer == inner {
+            return;
+        }
+        let rect = |[x, y, w, h]: [f32; 4]| {
+            format!("M{x} {y}h{}v{}h{}Z", w.max(0.0), h.max(0.0), -w.max(0.0))
+        };
+        writeln!(
+            self.svg,
+            r#"<path d="{}{}" fill="{fill}" fill-opacity="0.5" fill-rule="evenodd" />"#,
+            rect(outer),
+            rect(inner)
+        )
+        .unwrap();
+    }
+}
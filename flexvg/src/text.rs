@@ -0,0 +1,259 @@
+use crate::prelude::*;
+use std::{collections::HashMap, fs, path::Path};
+use taffy::prelude::AvailableSpace as TaffyAvailableSpace;
+use taffy::prelude::Size as TaffySize;
+use ttf_parser::Face;
+
+// @NOTE(jshrake): CSS initial values
+const DEFAULT_FONT_SIZE: f32 = 16.0;
+const DEFAULT_LINE_HEIGHT: f32 = 1.2;
+
+/// Font faces loaded from [`FlexRootData::fonts`].
+#[derive(Default)]
+pub(crate) struct FontBook {
+    faces: Vec<(String, Vec<u8>)>,
+}
+
+impl FontBook {
+    pub fn load(
+        fonts: &[FlexFontFace],
+        base_path: Option<&Path>,
+    ) -> Result<Self, Error> {
+        let mut faces = Vec::with_capacity(fonts.len());
+        for font in fonts {
+            let path = if let Some(base_path) = base_path {
+                base_path.join(&font.src)
+            } else {
+                Path::new(&font.src).to_path_buf()
+            };
+            let data = fs::read(path)?;
+            // Fail early instead of silently measuring with fallback metrics
+            Face::parse(&data, 0)?;
+            faces.push((font.family.clone(), data));
+        }
+        Ok(Self { faces })
+    }
+
+    /// Returns the measurable text of a `text` element, if any.
+    pub fn text(&self, element: &FlexElement) -> Option<FlexText> {
+        if element.element != "text" {
+            return None;
+        }
+        let value = element.value.clone().filter(|value| !value.is_empty())?;
+        let family = text_property(element, "font-family");
+        let font_size = text_property(element, "font-size")
+            .and_then(|size| parse_font_size(&size))
+            .unwrap_or(DEFAULT_FONT_SIZE);
+        // @NOTE(jshrake): Parse the face once here, rather than on every
+        // measure call
+        let advances = self
+            .find(family.as_deref())
+            .and_then(|data| Face::parse(data, 0).ok())
+            .map(|face| glyph_advances(&face, &value));
+        Some(FlexText {
+            value,
+            advances,
+            font_size,
+            line_height: element.line_height.unwrap_or(DEFAULT_LINE_HEIGHT),
+            white_space: element.white_space,
+        })
+    }
+
+    fn find(&self, family: Option<&str>) -> Option<&[u8]> {
+        let families =
+            family.into_iter().flat_map(|family| family.split(',')).map(
+                |family| family.trim().trim_matches(|c| c == '"' || c == '\''),
+            );
+        for family in families {
+            let face = self
+                .faces
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(family));
+            if let Some((_, data)) = face {
+                return Some(data);
+            }
+        }
+        self.faces.first().map(|(_, data)| data.as_slice())
+    }
+}
+
+/// The `value` of a `text` element along with the font used to measure it.
+#[derive(Clone)]
+pub(crate) struct FlexText {
+    value: String,
+    /// Advance of each character of `value` in em, from the configured font
+    advances: Option<HashMap<char, f32>>,
+    font_size: f32,
+    line_height: f32,
+    white_space: FlexWhiteSpace,
+}
+
+impl std::fmt::Debug for FlexText {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("FlexText")
+            .field("value", &self.value)
+            .field("font", &self.advances.is_some())
+            .field("font_size", &self.font_size)
+            .field("line_height", &self.line_height)
+            .field("white_space", &self.white_space)
+            .finish()
+    }
+}
+
+impl FlexText {
+    pub fn line_height(&self) -> f32 {
+        self.font_size * self.line_height
+    }
+
+    /// Width of a single line of text, in user units.
+    pub fn line_width(&self, line: &str) -> f32 {
+        let advance = |c: char| match &self.advances {
+            Some(advances) => advances.get(&c).copied().unwrap_or(1.0),
+            None => fallback_advance(c),
+        };
+        line.chars().map(|c| advance(c) * self.font_size).sum()
+    }
+
+    /// Greedily breaks the text at whitespace into lines no wider than
+    /// `width`, following `white-space`. A single word wider than `width`
+    /// gets a line of its own.
+    pub fn lines(&self, width: Option<f32>) -> Vec<String> {
+        let (width, paragraphs) = match self.white_space {
+            FlexWhiteSpace::Nowrap => return vec![self.value.clone()],
+            FlexWhiteSpace::Normal => (width, vec![self.value.as_str()]),
+            FlexWhiteSpace::PreLine => (width, self.value.lines().collect()),
+        };
+        let mut lines = vec![];
+        for paragraph in paragraphs {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                if line.is_empty() {
+                    line.push_str(word);
+                    continue;
+                }
+                let candidate = format!("{line} {word}");
+                match width {
+                    Some(width) if self.line_width(&candidate) > width => {
+                        let word = word.to_string();
+                        lines.push(std::mem::replace(&mut line, word));
+                    }
+                    _ => line = candidate,
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Breaks the text into lines that fit the laid out `width`.
+    pub fn layout(&self, width: f32) -> FlexTextLines {
+        let lines = self.lines(Some(width));
+        let width =
+            lines.iter().map(|line| self.line_width(line)).fold(0.0, f32::max);
+        FlexTextLines { lines, line_height: self.line_height(), width }
+    }
+
+    /// Taffy measure function: wraps the text to the available width, at
+    /// every opportunity under a min-content constraint and nowhere under a
+    /// max-content constraint.
+    pub fn measure(
+        &self,
+        known_dimensions: TaffySize<Option<f32>>,
+        available_space: TaffySize<TaffyAvailableSpace>,
+    ) -> TaffySize<f32> {
+        let available_width = match available_space.width {
+            TaffyAvailableSpace::Definite(width) => Some(width),
+            TaffyAvailableSpace::MinContent => Some(0.0),
+            TaffyAvailableSpace::MaxContent => None,
+        };
+        let lines = self.lines(known_dimensions.width.or(available_width));
+        let width =
+            lines.iter().map(|line| self.line_width(line)).fold(0.0, f32::max);
+        let height = lines.len() as f32 * self.line_height();
+        TaffySize {
+            width: known_dimensions.width.unwrap_or(width),
+            height: known_dimensions.height.unwrap_or(height),
+        }
+    }
+}
+
+/// Length of text that fits across a `width` by `height` box once `rotate`
+/// and `scale` turn its baseline, so rotated text wraps along the box.
+/// A free-form `transform` isn't taken into account.
+pub(crate) fn wrap_width(
+    width: f32,
+    height: f32,
+    transform: &FlexTransform,
+) -> f32 {
+    let (sin, cos) = transform.rotate.unwrap_or(0.0).to_radians().sin_cos();
+    let (scale_x, _) = transform.scale.unwrap_or((1.0, 1.0));
+    // @NOTE(jshrake): Snap to zero so a quarter turn gives exactly the
+    // height
+    let fit = |length: f32, ratio: f32| {
+        if ratio.abs() < 1e-6 {
+            f32::INFINITY
+        } else {
+            length / ratio.abs()
+        }
+    };
+    fit(width, cos).min(fit(height, sin)) / scale_x.abs()
+}
+
+/// Advances, in em, of the characters in `value`. Characters without a
+/// glyph are 1em wide.
+fn glyph_advances(face: &Face, value: &str) -> HashMap<char, f32> {
+    let units_per_em = face.units_per_em() as f32;
+    let advance = |c: char| {
+        let glyph = face.glyph_index(c)?;
+        face.glyph_hor_advance(glyph)
+    };
+    value
+        .chars()
+        .map(|c| (c, advance(c).map_or(1.0, |a| a as f32 / units_per_em)))
+        .collect()
+}
+
+/// Approximate advance, in em, used when no font is configured
+fn fallback_advance(c: char) -> f32 {
+    match c {
+        ' ' => 0.3,
+        // CJK, emoji and other wide characters
+        '\u{2e80}'.. => 1.0,
+        _ => 0.6,
+    }
+}
+
+/// Reads a text property from the element's `style` declarations, falling
+/// back to the presentation attribute of the same name.
+pub(crate) fn text_property(
+    element: &FlexElement,
+    name: &str,
+) -> Option<String> {
+    let from_style = element.attributes.get("style").and_then(|style| {
+        style.to_string().split(';').find_map(|declaration| {
+            let (property, value) = declaration.split_once(':')?;
+            (property.trim() == name).then(|| value.trim().to_string())
+        })
+    });
+    from_style.or_else(|| element.attributes.get(name).map(|v| v.to_string()))
+}
+
+/// Parses a CSS font-size into user units, with `em` and `%` relative to
+/// the initial font size.
+fn parse_font_size(size: &str) -> Option<f32> {
+    let size = size.trim();
+    let (value, scale) = if let Some(value) = size.strip_suffix("px") {
+        (value, 1.0)
+    } else if let Some(value) = size.strip_suffix("pt") {
+        (value, 4.0 / 3.0)
+    } else if let Some(value) = size.strip_suffix("rem") {
+        (value, DEFAULT_FONT_SIZE)
+    } else if let Some(value) = size.strip_suffix("em") {
+        (value, DEFAULT_FONT_SIZE)
+    } else if let Some(value) = size.strip_suffix('%') {
+        (value, DEFAULT_FONT_SIZE / 100.0)
+    } else {
+        (size, 1.0)
+    };
+    value.trim().parse::<f32>().ok().map(|value| value * scale)
+}
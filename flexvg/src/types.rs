@@ -1,3 +1,4 @@
+use crate::text::FlexText;
 #[cfg(feature = "serde")]
 use convert_case::{Case, Casing};
 #[cfg(feature = "serde")]
@@ -6,13 +7,15 @@ use lazy_static::lazy_static;
 use regex::Regex;
 #[cfg(feature = "serde")]
 use serde::{
-    de::{value::StringDeserializer, DeserializeOwned, IntoDeserializer},
+    de::{
+        value::{MapAccessDeserializer, StringDeserializer},
+        DeserializeOwned, IgnoredAny, IntoDeserializer, MapAccess, Visitor,
+    },
     Deserialize, Deserializer,
 };
 use std::collections::HashMap;
 #[cfg(feature = "serde")]
 use std::str::FromStr;
-use taffy::node::Node as TaffyNode;
 use taffy::prelude::*;
 
 /*
@@ -26,16 +29,49 @@ use taffy::prelude::*;
 888        888  "Y8888  888  888 888    Y888  "Y88P"   "Y88888  "Y8888
  */
 
-///
+/// A node in a flexvg document: an optional SVG element, its flexbox layout
+/// and its children.
 #[derive(Default, Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(default))]
 #[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
 pub struct FlexNode {
+    // @NOTE(jshrake): Flattened structs take their keys in field order, and
+    // the element keeps every key left, so node properties that aren't SVG
+    // attributes come first
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub decoration: FlexDecoration,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub transform: FlexTransform,
     #[cfg_attr(feature = "serde", serde(flatten))]
     pub element: Option<FlexElement>,
     #[cfg_attr(feature = "serde", serde(flatten))]
     pub layout: FlexLayout,
+    /// Stacking order among siblings: higher values draw on top, and ties
+    /// follow the root `paint-order`
+    pub z_index: i32,
+    /// Whether the node and its children are clipped to its box
+    pub overflow: FlexOverflow,
+    /// SVG `id` of the node, which root `edges` refer to
+    pub id: Option<String>,
+    /// Text centered in a `rect` that fills the node. Both are added after
+    /// the children, the text on top of them and the rect below.
+    pub label: Option<String>,
+    /// `class` of the label rect
+    pub label_class: Option<String>,
+    /// `style` of the label rect
+    pub label_style: Option<String>,
+    /// `class` of the label text, in addition to `flexvg-label`
+    pub label_text_class: Option<String>,
+    /// `style` of the label text
+    pub label_text_style: Option<String>,
+    /// Rotation of the label text, e.g. `90` for vertical text. Unlike
+    /// `rotate`, it leaves the rect as laid out.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_angle")
+    )]
+    pub label_rotate: Option<f32>,
     #[cfg_attr(feature = "serde", serde(flatten))]
     pub root: Option<FlexRootData>,
     #[cfg_attr(
@@ -45,12 +81,196 @@ pub struct FlexNode {
     pub nodes: Vec<FlexNode>,
 }
 
-#[derive(Default, Debug, Clone)]
+/// Document-level settings, read from the root node.
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(default))]
 #[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
 pub struct FlexRootData {
     pub stylesheet: Option<String>,
+    /// Size of the canvas the root node is laid out in, in user units. This
+    /// is also the width and height of the emitted SVG. A missing axis is
+    /// 100.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_canvas")
+    )]
+    pub canvas: FlexSize<f32>,
+    /// How the canvas is sized: `fixed` uses `canvas`, `shrink-to-fit` lays
+    /// the root out with undefined available space and takes its size.
+    pub canvas_sizing: FlexCanvasSizing,
+    /// Size of `1em` and `1rem` in layout lengths
+    pub root_font_size: f32,
+    /// How nodes without a `width` or `height` are sized
+    pub defaults: FlexDefaults,
+    /// Whether the first or the last of a node's children draws on top,
+    /// among children with the same `z-index`
+    pub paint_order: FlexPaintOrder,
+    /// Draws the box, edges and size of every node over the diagram
+    pub debug: bool,
+    /// SVG `viewBox` as `[min-x, min-y, width, height]`. Defaults to the
+    /// canvas.
+    pub view_box: Option<(f32, f32, f32, f32)>,
+    /// SVG `preserveAspectRatio`, e.g. `xMidYMid meet`.
+    pub preserve_aspect_ratio: Option<String>,
+    /// Fonts used to measure `text` elements, matched against their
+    /// `font-family`. The first face is used when nothing matches.
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "Vec::is_empty", default)
+    )]
+    pub fonts: Vec<FlexFontFace>,
+    /// Arrows drawn between nodes with an `id`, over the laid out diagram
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "Vec::is_empty", default)
+    )]
+    pub edges: Vec<FlexEdge>,
+    /// Clearance routed edges keep around boxes, and distance between
+    /// edges joining the same nodes. Can't be negative.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_edge_spacing")
+    )]
+    pub edge_spacing: f32,
+}
+
+impl Default for FlexRootData {
+    fn default() -> Self {
+        Self {
+            stylesheet: None,
+            canvas: FlexSize { width: CANVAS_SIZE, height: CANVAS_SIZE },
+            canvas_sizing: Default::default(),
+            root_font_size: 16.0,
+            defaults: Default::default(),
+            paint_order: Default::default(),
+            debug: false,
+            view_box: None,
+            preserve_aspect_ratio: None,
+            fonts: Vec::new(),
+            edges: Vec::new(),
+            edge_spacing: 4.0,
+        }
+    }
+}
+
+const CANVAS_SIZE: f32 = 100.0;
+
+#[cfg(feature = "serde")]
+fn deserialize_edge_spacing<'de, D>(deserializer: D) -> Result<f32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let spacing = f32::deserialize(deserializer)?;
+    if spacing >= 0.0 {
+        Ok(spacing)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "edge-spacing: expected a length of at least 0, found {spacing}"
+        )))
+    }
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_canvas<'de, D>(
+    deserializer: D,
+) -> Result<FlexSize<f32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Canvas {
+        width: Option<f32>,
+        height: Option<f32>,
+    }
+    let Canvas { width, height } = Canvas::deserialize(deserializer)?;
+    Ok(FlexSize {
+        width: width.unwrap_or(CANVAS_SIZE),
+        height: height.unwrap_or(CANVAS_SIZE),
+    })
+}
+
+/// Initial values of properties a node doesn't set.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub enum FlexDefaults {
+    /// Nodes fill their parent: sizes default to `100%`
+    #[default]
+    Flexvg,
+    /// Browser defaults: sizes default to `auto`
+    Css,
+}
+
+impl FlexDefaults {
+    /// The size of a node that doesn't set one
+    pub fn size(self) -> FlexDimension {
+        match self {
+            FlexDefaults::Flexvg => FlexDimension::default(),
+            FlexDefaults::Css => FlexDimension::Auto,
+        }
+    }
+}
+
+/// An arrow from the node with `id` equal to `from` to the one with `id`
+/// equal to `to`, clipped at the borders of their boxes. Boxes are taken as
+/// laid out, before any `rotate`, `scale` or `transform`.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub struct FlexEdge {
+    pub from: String,
+    pub to: String,
+    /// Text drawn at the middle of the edge
+    pub label: Option<String>,
+    /// `class` of the edge group, in addition to `flexvg-edge`
+    pub class: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub routing: FlexRouting,
+    /// Radius of the bends of a routed edge
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub radius: f32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub enum FlexRouting {
+    /// A straight line between the boxes
+    #[default]
+    Straight,
+    /// Horizontal and vertical segments around the boxes of other nodes,
+    /// with as few bends as possible, inside the root node's box. Straight
+    /// when there is no way around.
+    Orthogonal,
+}
+
+/// A TrueType or OpenType font file, relative to the document base path.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FlexFontFace {
+    pub family: String,
+    pub src: String,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub enum FlexPaintOrder {
+    /// Earlier siblings draw over later ones
+    #[default]
+    FirstOnTop,
+    /// Later siblings draw over earlier ones, as in SVG and HTML
+    LastOnTop,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub enum FlexCanvasSizing {
+    #[default]
+    Fixed,
+    ShrinkToFit,
 }
 
 /*
@@ -66,19 +286,78 @@ pub struct FlexRootData {
 
 
  */
-#[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+/// A laid out [`FlexNode`].
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(default))]
 #[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
 pub struct FlexGeomNode {
     pub width: f32,
     pub height: f32,
+    /// Position relative to the parent node
     pub position: FlexPoint,
+    /// Position relative to the root node
+    pub absolute_position: FlexPoint,
+    /// Resolved padding widths
+    pub padding: FlexRect<f32>,
+    /// Resolved border widths
+    pub border: FlexRect<f32>,
+    /// Margin widths as laid out, including the space taken by `auto`
+    /// margins
+    pub margin: FlexRect<f32>,
+    /// Resolved horizontal and vertical radius of the corners
+    pub border_radius: FlexSize<f32>,
+    pub decoration: FlexDecoration,
     pub element: Option<FlexElement>,
+    pub text: Option<FlexTextLines>,
+    pub id: Option<String>,
+    pub transform: FlexTransform,
+    pub z_index: i32,
+    pub overflow: FlexOverflow,
     pub root: Option<FlexRootData>,
+    /// Whether the node is the text or rect added for its parent's `label`
+    pub is_label: bool,
     pub nodes: Vec<FlexGeomNode>,
 }
 
+impl FlexGeomNode {
+    /// Position of the content box, inside the border and padding, relative
+    /// to the root node
+    pub fn content_position(&self) -> FlexPoint {
+        FlexPoint {
+            x: self.absolute_position.x
+                + self.border.start
+                + self.padding.start,
+            y: self.absolute_position.y + self.border.top + self.padding.top,
+        }
+    }
+
+    /// Size of the content box, inside the border and padding
+    pub fn content_size(&self) -> FlexSize<f32> {
+        let (border, padding) = (self.border, self.padding);
+        let horizontal =
+            border.start + border.end + padding.start + padding.end;
+        let vertical =
+            border.top + border.bottom + padding.top + padding.bottom;
+        FlexSize {
+            width: (self.width - horizontal).max(0.0),
+            height: (self.height - vertical).max(0.0),
+        }
+    }
+}
+
+/// The `value` of a `text` element broken into lines that fit its laid out
+/// width.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub struct FlexTextLines {
+    pub lines: Vec<String>,
+    pub line_height: f32,
+    /// Width of the widest line
+    pub width: f32,
+}
+
 /*
 8888888888 888                   8888888888 888                                          888
 888        888                   888        888                                          888
@@ -96,10 +375,64 @@ pub struct FlexElement {
     pub element: String,
     pub origin: Option<(f32, f32)>,
     pub value: Option<String>,
-    #[cfg_attr(feature = "serde", serde(flatten))]
+    /// Line height of wrapped `text`, as a multiple of the font size
+    #[cfg_attr(feature = "serde", serde(rename = "line-height"))]
+    pub line_height: Option<f32>,
+    /// Horizontal alignment of wrapped `text` lines within the box
+    #[cfg_attr(feature = "serde", serde(rename = "text-align"))]
+    pub text_align: Option<FlexTextAlign>,
+    /// How `text` breaks into lines
+    #[cfg_attr(feature = "serde", serde(rename = "white-space", default))]
+    pub white_space: FlexWhiteSpace,
+    /// Points of a `polyline` or `polygon`
+    pub points: Option<FlexPoints>,
+    /// Data of a `path` in box coordinates, e.g. `M0 0 L1 0.5 L0 1 Z`
+    pub path: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(flatten, deserialize_with = "deserialize_attributes")
+    )]
     pub attributes: HashMap<String, FlexElementAttributeValue>,
 }
 
+/// The `points` of a `polyline` or `polygon`, either relative to the laid
+/// out box or as plain SVG
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum FlexPoints {
+    /// Points in box coordinates, from `[0, 0]` at the top left to `[1, 1]`
+    /// at the bottom right
+    Box(Vec<(f32, f32)>),
+    /// SVG points in user units, e.g. `0,0 10,0 5,10`, written as is
+    Svg(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub enum FlexTextAlign {
+    #[cfg_attr(feature = "serde", serde(alias = "left"))]
+    Start,
+    Center,
+    #[cfg_attr(feature = "serde", serde(alias = "right"))]
+    End,
+}
+
+/// CSS `white-space` values that control line breaks in `text`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub enum FlexWhiteSpace {
+    /// A single line, as written
+    #[default]
+    Nowrap,
+    /// Wraps to the width of the box
+    Normal,
+    /// Wraps to the width of the box and breaks at newlines
+    PreLine,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(untagged))]
@@ -109,6 +442,32 @@ pub enum FlexElementAttributeValue {
     I32(i32),
 }
 
+/// Collects the scalar attributes of an element. Maps and lists, e.g. the
+/// `padding` of the node, aren't SVG attributes and are skipped.
+#[cfg(feature = "serde")]
+fn deserialize_attributes<'de, D>(
+    deserializer: D,
+) -> Result<HashMap<String, FlexElementAttributeValue>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MaybeAttribute {
+        Value(FlexElementAttributeValue),
+        Other(IgnoredAny),
+    }
+    let attributes =
+        HashMap::<String, MaybeAttribute>::deserialize(deserializer)?;
+    Ok(attributes
+        .into_iter()
+        .filter_map(|(name, value)| match value {
+            MaybeAttribute::Value(value) => Some((name, value)),
+            MaybeAttribute::Other(_) => None,
+        })
+        .collect())
+}
+
 impl std::fmt::Display for FlexElementAttributeValue {
     // This trait requires `fmt` with this exact signature.
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -134,24 +493,21 @@ impl std::fmt::Display for FlexElementAttributeValue {
                                                     "Y88P"
  */
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(default))]
 #[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
 pub struct FlexLayout {
-    /*
-    // @TODO(jshrake): https://github.com/DioxusLabs/taffy/pull/153
     #[cfg_attr(
         feature = "serde",
         serde(deserialize_with = "deserialize_simple_enum_as_pascal")
     )]
-    */
     pub display: Display,
     #[cfg_attr(
         feature = "serde",
         serde(deserialize_with = "deserialize_simple_enum_as_pascal")
     )]
-    pub position_type: PositionType,
+    pub position_type: Position,
     #[cfg_attr(
         feature = "serde",
         serde(deserialize_with = "deserialize_simple_enum_as_pascal")
@@ -164,88 +520,317 @@ pub struct FlexLayout {
     pub flex_wrap: FlexWrap,
     #[cfg_attr(
         feature = "serde",
-        serde(deserialize_with = "deserialize_simple_enum_as_pascal")
+        serde(deserialize_with = "deserialize_optional_simple_enum_as_pascal")
     )]
-    pub align_items: AlignItems,
+    pub align_items: Option<AlignItems>,
     #[cfg_attr(
         feature = "serde",
-        serde(deserialize_with = "deserialize_simple_enum_as_pascal")
+        serde(deserialize_with = "deserialize_optional_simple_enum_as_pascal")
     )]
-    pub align_self: AlignSelf,
+    pub align_self: Option<AlignSelf>,
     #[cfg_attr(
         feature = "serde",
-        serde(deserialize_with = "deserialize_simple_enum_as_pascal")
+        serde(deserialize_with = "deserialize_optional_simple_enum_as_pascal")
     )]
-    pub align_content: AlignContent,
+    pub align_content: Option<AlignContent>,
     #[cfg_attr(
         feature = "serde",
-        serde(deserialize_with = "deserialize_simple_enum_as_pascal")
+        serde(deserialize_with = "deserialize_optional_simple_enum_as_pascal")
+    )]
+    pub justify_content: Option<JustifyContent>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_optional_simple_enum_as_pascal")
+    )]
+    pub justify_items: Option<JustifyItems>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_optional_simple_enum_as_pascal")
+    )]
+    pub justify_self: Option<JustifySelf>,
+    /// Offsets of a positioned node, also accepted as `inset`
+    #[cfg_attr(
+        feature = "serde",
+        serde(alias = "inset", deserialize_with = "deserialize_position")
     )]
-    pub justify_content: JustifyContent,
-    pub position: Rect<Dimension>,
-    pub margin: Rect<Dimension>,
-    pub padding: Rect<Dimension>,
-    pub border: Rect<Dimension>,
+    pub position: FlexRect<Option<FlexDimension>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_margin")
+    )]
+    pub margin: FlexRect<Option<FlexDimension>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_padding")
+    )]
+    pub padding: FlexRect<Option<FlexDimension>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_border")
+    )]
+    pub border: FlexRect<Option<FlexDimension>>,
+    /// Sets `row-gap` and `column-gap`, e.g. `10 20`, or both with one
+    /// length
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_gap"))]
+    pub gap: Option<FlexGap>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_row_gap")
+    )]
+    pub row_gap: Option<FlexDimension>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_column_gap")
+    )]
+    pub column_gap: Option<FlexDimension>,
+    /// Sets `flex-grow`, `flex-shrink` and `flex-basis`, e.g. `1 1 auto`.
+    /// Unlike CSS, where the last declaration wins, `flex` overrides the
+    /// longhands wherever they appear.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_flex")
+    )]
+    pub flex: Option<FlexShorthand>,
     pub flex_grow: f32,
     pub flex_shrink: f32,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_flex_basis")
+    )]
     pub flex_basis: FlexDimension,
     #[cfg_attr(feature = "serde", serde(flatten))]
-    pub size: FlexSize<FlexDimension>,
-    pub min_size: Size<Dimension>,
-    pub max_size: Size<Dimension>,
-    pub aspect_ratio: Number,
+    /// `width` and `height`, resolved by [`FlexDefaults`] when unset
+    pub size: FlexSize<Option<FlexDimension>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_min_size")
+    )]
+    pub min_size: FlexSize<Option<FlexDimension>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_max_size")
+    )]
+    pub max_size: FlexSize<Option<FlexDimension>>,
+    pub aspect_ratio: Option<f32>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::grid::deserialize_track_list")
+    )]
+    pub grid_template_rows: Vec<TrackSizingFunction>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::grid::deserialize_track_list")
+    )]
+    pub grid_template_columns: Vec<TrackSizingFunction>,
+    /// Named areas, one string per row, e.g. `["head head", "nav main"]`
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::grid::deserialize_template_areas")
+    )]
+    pub grid_template_areas: Vec<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::grid::deserialize_auto_track_list")
+    )]
+    pub grid_auto_rows: Vec<NonRepeatedTrackSizingFunction>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::grid::deserialize_auto_track_list")
+    )]
+    pub grid_auto_columns: Vec<NonRepeatedTrackSizingFunction>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_simple_enum_as_pascal")
+    )]
+    pub grid_auto_flow: GridAutoFlow,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::grid::deserialize_placement")
+    )]
+    pub grid_row: Line<GridPlacement>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::grid::deserialize_placement")
+    )]
+    pub grid_column: Line<GridPlacement>,
+    /// Name of an area in the parent's `grid-template-areas`
+    pub grid_area: Option<String>,
 }
 
 impl Default for FlexLayout {
     fn default() -> Self {
         Self {
             display: Default::default(),
-            position_type: Default::default(),
+            position_type: Position::Relative,
             flex_direction: Default::default(),
             flex_wrap: Default::default(),
-            align_items: Default::default(),
-            align_self: Default::default(),
-            align_content: Default::default(),
-            justify_content: Default::default(),
+            align_items: None,
+            align_self: None,
+            align_content: None,
+            justify_content: None,
+            justify_items: None,
+            justify_self: None,
             position: Default::default(),
             margin: Default::default(),
             padding: Default::default(),
             border: Default::default(),
+            gap: None,
+            row_gap: None,
+            column_gap: None,
+            flex: None,
             flex_grow: 0.0,
             flex_shrink: 1.0,
             flex_basis: FlexDimension::Auto,
             size: Default::default(),
             min_size: Default::default(),
             max_size: Default::default(),
-            aspect_ratio: Default::default(),
+            aspect_ratio: None,
+            grid_template_rows: Vec::new(),
+            grid_template_columns: Vec::new(),
+            grid_template_areas: Vec::new(),
+            grid_auto_rows: Vec::new(),
+            grid_auto_columns: Vec::new(),
+            grid_auto_flow: Default::default(),
+            grid_row: Line {
+                start: GridPlacement::Auto,
+                end: GridPlacement::Auto,
+            },
+            grid_column: Line {
+                start: GridPlacement::Auto,
+                end: GridPlacement::Auto,
+            },
+            grid_area: None,
         }
     }
 }
 
-impl From<FlexLayout> for Style {
-    fn from(s: FlexLayout) -> Style {
+impl FlexLayout {
+    /// Converts to a Taffy style, resolving relative units against `units`
+    pub fn to_style(self, units: &FlexUnits) -> Style {
+        let width = units.container.map(|size| size.width);
+        let height = units.container.map(|size| size.height);
+        let main = match units.container_direction {
+            FlexDirection::Row | FlexDirection::RowReverse => width,
+            FlexDirection::Column | FlexDirection::ColumnReverse => height,
+        };
+        let dimension = |d: FlexDimension, basis| d.resolve(units, basis);
+        let size = units.defaults.size();
+        let optional_dimension = |d: Option<FlexDimension>, basis| {
+            d.map_or(Dimension::Auto, |d| d.resolve(units, basis))
+        };
+        // @NOTE(jshrake): Percentage margins, padding and borders resolve
+        // against the width of the parent, even vertically
+        let length_percentage = |d: Option<FlexDimension>, basis| {
+            d.map_or(LengthPercentage::Points(0.0), |d| {
+                d.resolve_length_percentage(units, basis)
+            })
+        };
+        let inset = |d: Option<FlexDimension>, basis| {
+            d.map_or(LengthPercentageAuto::Auto, |d| {
+                d.resolve_length_percentage_auto(units, basis)
+            })
+        };
+        let margin = |d: Option<FlexDimension>| {
+            d.map_or(LengthPercentageAuto::Points(0.0), |d| {
+                d.resolve_length_percentage_auto(units, width)
+            })
+        };
+        let FlexShorthand {
+            grow: flex_grow,
+            shrink: flex_shrink,
+            basis: flex_basis,
+        } = self.flex.unwrap_or(FlexShorthand {
+            grow: self.flex_grow,
+            shrink: self.flex_shrink,
+            basis: self.flex_basis,
+        });
         Style {
-            display: s.display,
-            position_type: s.position_type,
-            flex_direction: s.flex_direction,
-            flex_wrap: s.flex_wrap,
-            align_items: s.align_items,
-            align_self: s.align_self,
-            align_content: s.align_content,
-            justify_content: s.justify_content,
-            position: s.position,
-            margin: s.margin,
-            padding: s.padding,
-            border: s.border,
-            flex_grow: s.flex_grow,
-            flex_shrink: s.flex_shrink,
-            flex_basis: s.flex_basis.into(),
-            size: s.size.into(),
-            min_size: s.min_size,
-            max_size: s.max_size,
-            aspect_ratio: s.aspect_ratio,
+            display: self.display,
+            position: self.position_type,
+            flex_direction: self.flex_direction,
+            flex_wrap: self.flex_wrap,
+            align_items: self.align_items,
+            align_self: self.align_self,
+            align_content: self.align_content,
+            justify_content: self.justify_content,
+            justify_items: self.justify_items,
+            justify_self: self.justify_self,
+            inset: Rect {
+                left: inset(self.position.start, width),
+                right: inset(self.position.end, width),
+                top: inset(self.position.top, height),
+                bottom: inset(self.position.bottom, height),
+            },
+            margin: self.margin.map(margin),
+            padding: self.padding.map(|d| length_percentage(d, width)),
+            border: self.border.map(|d| length_percentage(d, width)),
+            gap: Size {
+                width: length_percentage(
+                    self.column_gap.or(self.gap.map(|gap| gap.column)),
+                    width,
+                ),
+                height: length_percentage(
+                    self.row_gap.or(self.gap.map(|gap| gap.row)),
+                    height,
+                ),
+            },
+            flex_grow,
+            flex_shrink,
+            flex_basis: dimension(flex_basis, main),
+            size: Size {
+                width: dimension(self.size.width.unwrap_or(size), width),
+                height: dimension(self.size.height.unwrap_or(size), height),
+            },
+            min_size: Size {
+                width: optional_dimension(self.min_size.width, width),
+                height: optional_dimension(self.min_size.height, height),
+            },
+            max_size: Size {
+                width: optional_dimension(self.max_size.width, width),
+                height: optional_dimension(self.max_size.height, height),
+            },
+            aspect_ratio: self.aspect_ratio,
+            grid_template_rows: self.grid_template_rows,
+            grid_template_columns: self.grid_template_columns,
+            grid_auto_rows: self.grid_auto_rows,
+            grid_auto_columns: self.grid_auto_columns,
+            grid_auto_flow: self.grid_auto_flow,
+            grid_row: self.grid_row,
+            grid_column: self.grid_column,
         }
     }
+
+    /// Whether any dimension is a `calc()` expression, which needs the size
+    /// of the parent to resolve
+    pub fn has_calc(&self) -> bool {
+        let rect = |r: &FlexRect<Option<FlexDimension>>| {
+            [r.start, r.end, r.top, r.bottom]
+        };
+        let size = |s: &FlexSize<Option<FlexDimension>>| [s.width, s.height];
+        rect(&self.position)
+            .into_iter()
+            .chain(rect(&self.margin))
+            .chain(rect(&self.padding))
+            .chain(rect(&self.border))
+            .chain([self.row_gap, self.column_gap])
+            .chain([
+                self.gap.map(|gap| gap.row),
+                self.gap.map(|gap| gap.column),
+            ])
+            .chain(size(&self.size))
+            .chain(size(&self.min_size))
+            .chain(size(&self.max_size))
+            .flatten()
+            .chain(self.flex.map(|flex| flex.basis))
+            .chain([self.flex_basis])
+            .any(|d| d.is_calc())
+    }
+}
+
+impl From<FlexLayout> for Style {
+    fn from(s: FlexLayout) -> Style {
+        s.to_style(&FlexUnits::default())
+    }
 }
 
 // @NOTE(jshrake):
@@ -264,6 +849,24 @@ where
     T::deserialize(deserializer).map_err(serde::de::Error::custom)
 }
 
+/// Like [`deserialize_simple_enum_as_pascal`], with `auto` as `None`
+#[cfg(feature = "serde")]
+pub fn deserialize_optional_simple_enum_as_pascal<'de, T, D>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error>
+where
+    T: DeserializeOwned,
+    D: Deserializer<'de>,
+{
+    let val = String::deserialize(deserializer)?;
+    if val == "auto" {
+        return Ok(None);
+    }
+    let pascal = val.to_case(Case::Pascal);
+    let deserializer: StringDeserializer<D::Error> = pascal.into_deserializer();
+    T::deserialize(deserializer).map(Some).map_err(serde::de::Error::custom)
+}
+
 /*
 8888888888 888                    .d8888b.  d8b
 888        888                   d88P  Y88b Y8P
@@ -275,32 +878,69 @@ where
 888        888  "Y8888  888  888  "Y8888P"  888 88888888  "Y8888
 */
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(default))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(deserialize = "T: Deserialize<'de> + Default"))
+)]
 pub struct FlexSize<T> {
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_width")
+    )]
     pub width: T,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_height")
+    )]
     pub height: T,
 }
 
-impl Default for FlexSize<f32> {
-    fn default() -> Self {
-        FlexSize { width: 0.0, height: 0.0 }
+impl<T, U: std::convert::From<T>> From<FlexSize<T>> for Size<U> {
+    fn from(s: FlexSize<T>) -> Size<U> {
+        Size { width: s.width.into(), height: s.height.into() }
     }
 }
 
-impl Default for FlexSize<FlexDimension> {
-    fn default() -> Self {
-        FlexSize {
-            width: FlexDimension::default(),
-            height: FlexDimension::default(),
-        }
-    }
+/// Edges of a box, using Taffy's logical names. `left` and `right` are
+/// accepted for `start` and `end`.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(deserialize = "T: Deserialize<'de> + Default"))
+)]
+pub struct FlexRect<T> {
+    #[cfg_attr(
+        feature = "serde",
+        serde(alias = "left", deserialize_with = "deserialize_start")
+    )]
+    pub start: T,
+    #[cfg_attr(
+        feature = "serde",
+        serde(alias = "right", deserialize_with = "deserialize_end")
+    )]
+    pub end: T,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_top"))]
+    pub top: T,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_bottom")
+    )]
+    pub bottom: T,
 }
 
-impl<T, U: std::convert::From<T>> From<FlexSize<T>> for Size<U> {
-    fn from(s: FlexSize<T>) -> Size<U> {
-        Size { width: s.width.into(), height: s.height.into() }
+impl<T> FlexRect<T> {
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Rect<U> {
+        Rect {
+            left: f(self.start),
+            right: f(self.end),
+            top: f(self.top),
+            bottom: f(self.bottom),
+        }
     }
 }
 
@@ -315,82 +955,983 @@ impl<T, U: std::convert::From<T>> From<FlexSize<T>> for Size<U> {
 888        888  "Y8888  888  888 8888888P"  888 888  888  888  "Y8888  888  888  88888P' 888  "Y88P"  888  888
  */
 
+/// A CSS length: `auto`, a percentage of the parent, a length in user
+/// units (`pt`, `px` or a bare number), root font sizes (`em`, `rem`) or
+/// percentages of the canvas (`vw`, `vh`), or a `calc()` expression.
 #[derive(Debug, Copy, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
-#[cfg_attr(feature = "serde", serde(untagged))]
 pub enum FlexDimension {
     Auto,
-    #[cfg_attr(
-        feature = "serde",
-        serde(deserialize_with = "deserialize_percent")
-    )]
     Percent(f32),
-    #[cfg_attr(
-        feature = "serde",
-        serde(deserialize_with = "deserialize_point")
-    )]
     Points(f32),
+    Em(f32),
+    Vw(f32),
+    Vh(f32),
+    Calc(FlexCalc),
 }
 
+/// The flexvg default, see [`FlexDefaults`]
 impl Default for FlexDimension {
     fn default() -> Self {
         FlexDimension::Percent(1.0)
-        //FlexDimension::Auto
     }
 }
 
-impl From<FlexDimension> for Dimension {
-    fn from(s: FlexDimension) -> Dimension {
-        match s {
+impl FlexDimension {
+    /// Resolves relative units into a Taffy dimension. Percentages inside
+    /// `calc()` resolve against `basis`, when known.
+    pub fn resolve(self, units: &FlexUnits, basis: Option<f32>) -> Dimension {
+        match self {
             FlexDimension::Auto => Dimension::Auto,
             FlexDimension::Percent(value) => Dimension::Percent(value),
-            FlexDimension::Points(value) => Dimension::Points(value),
+            FlexDimension::Calc(calc) => calc.resolve(units, basis),
+            length => Dimension::Points(
+                FlexCalc::try_from(length)
+                    .map_or(0.0, |calc| calc.resolve_length(units)),
+            ),
         }
     }
-}
 
-#[cfg(feature = "serde")]
-lazy_static! {
-    static ref RE_F32: Regex = Regex::new(r"(\d[-+]?[0-9]*\.?[0-9]+)").unwrap();
-}
+    pub fn resolve_length_percentage_auto(
+        self,
+        units: &FlexUnits,
+        basis: Option<f32>,
+    ) -> LengthPercentageAuto {
+        match self.resolve(units, basis) {
+            Dimension::Auto => LengthPercentageAuto::Auto,
+            Dimension::Percent(value) => LengthPercentageAuto::Percent(value),
+            Dimension::Points(value) => LengthPercentageAuto::Points(value),
+        }
+    }
 
-#[cfg(feature = "serde")]
-fn deserialize_percent<'de, D>(deserializer: D) -> Result<f32, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    // @TODO(jshrake): String allocation
-    // https://users.rust-lang.org/t/serde-deserialize-string-field-in-json-to-a-different-type/12942/13
-    let s = String::deserialize(deserializer)?;
-    if !s.ends_with('%') {
-        return Err(serde::de::Error::custom("expecting %"));
+    // @NOTE(jshrake): padding and border can't be auto, treat it as zero
+    pub fn resolve_length_percentage(
+        self,
+        units: &FlexUnits,
+        basis: Option<f32>,
+    ) -> LengthPercentage {
+        match self.resolve(units, basis) {
+            Dimension::Auto => LengthPercentage::Points(0.0),
+            Dimension::Percent(value) => LengthPercentage::Percent(value),
+            Dimension::Points(value) => LengthPercentage::Points(value),
+        }
     }
-    let cap = RE_F32
-        .captures(&s)
-        .and_then(|c| c.get(1))
-        .ok_or_else(|| serde::de::Error::custom("invalid percent"))?;
-    f32::from_str(cap.as_str())
-        .map(|value| (value / 100.0))
-        .map_err(serde::de::Error::custom)
-}
+
+    pub fn is_calc(&self) -> bool {
+        matches!(self, FlexDimension::Calc(_))
+    }
+}
+
+impl std::fmt::Display for FlexDimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FlexDimension::Auto => write!(f, "auto"),
+            FlexDimension::Percent(value) => write!(f, "{}%", value * 100.0),
+            FlexDimension::Points(value) => write!(f, "{}pt", value),
+            FlexDimension::Em(value) => write!(f, "{}em", value),
+            FlexDimension::Vw(value) => write!(f, "{}vw", value),
+            FlexDimension::Vh(value) => write!(f, "{}vh", value),
+            FlexDimension::Calc(calc) => write!(f, "{}", calc),
+        }
+    }
+}
+
+impl From<FlexDimension> for Dimension {
+    fn from(s: FlexDimension) -> Dimension {
+        s.resolve(&FlexUnits::default(), None)
+    }
+}
+
+impl From<FlexDimension> for LengthPercentageAuto {
+    fn from(s: FlexDimension) -> LengthPercentageAuto {
+        s.resolve_length_percentage_auto(&FlexUnits::default(), None)
+    }
+}
+
+impl From<FlexDimension> for LengthPercentage {
+    fn from(s: FlexDimension) -> LengthPercentage {
+        s.resolve_length_percentage(&FlexUnits::default(), None)
+    }
+}
+
+/// A `calc()` expression, as the sum of a length in each unit.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct FlexCalc {
+    /// Fraction of the parent's size
+    pub percent: f32,
+    pub points: f32,
+    pub em: f32,
+    pub vw: f32,
+    pub vh: f32,
+}
+
+impl std::ops::Add for FlexCalc {
+    type Output = FlexCalc;
+
+    fn add(self, other: FlexCalc) -> FlexCalc {
+        FlexCalc {
+            percent: self.percent + other.percent,
+            points: self.points + other.points,
+            em: self.em + other.em,
+            vw: self.vw + other.vw,
+            vh: self.vh + other.vh,
+        }
+    }
+}
+
+impl std::ops::Mul<f32> for FlexCalc {
+    type Output = FlexCalc;
+
+    fn mul(self, factor: f32) -> FlexCalc {
+        FlexCalc {
+            percent: self.percent * factor,
+            points: self.points * factor,
+            em: self.em * factor,
+            vw: self.vw * factor,
+            vh: self.vh * factor,
+        }
+    }
+}
+
+impl FlexCalc {
+    /// The length of every term except the percentage
+    pub fn resolve_length(&self, units: &FlexUnits) -> f32 {
+        self.points
+            + self.em * units.font_size
+            + self.vw * units.viewport.width / 100.0
+            + self.vh * units.viewport.height / 100.0
+    }
+
+    /// Resolves to a length when `basis` is known. Until then, a mixed
+    /// expression is approximated by its percentage.
+    pub fn resolve(&self, units: &FlexUnits, basis: Option<f32>) -> Dimension {
+        match basis {
+            Some(basis) => Dimension::Points(
+                self.percent * basis + self.resolve_length(units),
+            ),
+            None if self.percent == 0.0 => {
+                Dimension::Points(self.resolve_length(units))
+            }
+            None => Dimension::Percent(self.percent),
+        }
+    }
+
+    /// The equivalent plain dimension, if only one unit is used
+    pub fn simplify(self) -> FlexDimension {
+        let terms = [
+            (self.percent, FlexDimension::Percent as fn(f32) -> FlexDimension),
+            (self.points, FlexDimension::Points),
+            (self.em, FlexDimension::Em),
+            (self.vw, FlexDimension::Vw),
+            (self.vh, FlexDimension::Vh),
+        ];
+        let mut used = terms.iter().filter(|(value, _)| *value != 0.0);
+        match (used.next(), used.next()) {
+            (None, _) => FlexDimension::Points(0.0),
+            (Some((value, dimension)), None) => dimension(*value),
+            _ => FlexDimension::Calc(self),
+        }
+    }
+}
+
+impl TryFrom<FlexDimension> for FlexCalc {
+    type Error = String;
+
+    fn try_from(dimension: FlexDimension) -> Result<Self, Self::Error> {
+        let calc = FlexCalc::default();
+        Ok(match dimension {
+            FlexDimension::Auto => return Err("auto in calc()".into()),
+            FlexDimension::Percent(percent) => FlexCalc { percent, ..calc },
+            FlexDimension::Points(points) => FlexCalc { points, ..calc },
+            FlexDimension::Em(em) => FlexCalc { em, ..calc },
+            FlexDimension::Vw(vw) => FlexCalc { vw, ..calc },
+            FlexDimension::Vh(vh) => FlexCalc { vh, ..calc },
+            FlexDimension::Calc(calc) => calc,
+        })
+    }
+}
+
+impl std::fmt::Display for FlexCalc {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let terms = [
+            (self.percent * 100.0, "%"),
+            (self.points, "pt"),
+            (self.em, "em"),
+            (self.vw, "vw"),
+            (self.vh, "vh"),
+        ];
+        write!(f, "calc(")?;
+        let mut first = true;
+        for (value, unit) in terms.iter().filter(|(value, _)| *value != 0.0) {
+            match (first, *value < 0.0) {
+                (true, _) => write!(f, "{value}{unit}")?,
+                (false, true) => write!(f, " - {}{unit}", -value)?,
+                (false, false) => write!(f, " + {value}{unit}")?,
+            }
+            first = false;
+        }
+        if first {
+            write!(f, "0pt")?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Document context needed to resolve a [`FlexDimension`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FlexUnits {
+    /// How unset sizes resolve
+    pub defaults: FlexDefaults,
+    /// Size of `1em` and `1rem`
+    pub font_size: f32,
+    /// Size of `100vw` and `100vh`
+    pub viewport: FlexSize<f32>,
+    /// Content box of the parent, which percentages inside `calc()` resolve
+    /// against. `None` until the parent has been laid out.
+    pub container: Option<FlexSize<f32>>,
+    /// Direction of the parent, whose main size `flex-basis` resolves
+    /// against
+    pub container_direction: FlexDirection,
+}
+
+impl Default for FlexUnits {
+    fn default() -> Self {
+        Self {
+            defaults: Default::default(),
+            font_size: 16.0,
+            viewport: FlexSize { width: 100.0, height: 100.0 },
+            container: None,
+            container_direction: FlexDirection::Row,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+lazy_static! {
+    static ref RE_LENGTH: Regex = Regex::new(
+        r"^([-+]?(?:\d+\.?\d*|\.\d+)(?:[eE][-+]?\d+)?)\s*([a-z%]*)$"
+    )
+    .unwrap();
+}
+
+#[cfg(feature = "serde")]
+impl FromStr for FlexDimension {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "auto" {
+            return Ok(FlexDimension::Auto);
+        }
+        let invalid = || {
+            format!(
+                "invalid length `{s}`, expected auto, calc() or a number \
+                 with an optional unit: %, pt, px, em, rem, vw or vh"
+            )
+        };
+        if s.starts_with("calc(") {
+            return crate::calc::parse_calc(s)
+                .map(FlexCalc::simplify)
+                .map_err(|e| format!("invalid length `{s}`: {e}"));
+        }
+        let caps = RE_LENGTH.captures(s).ok_or_else(invalid)?;
+        let value = f32::from_str(&caps[1]).map_err(|_| invalid())?;
+        match &caps[2] {
+            "%" => Ok(FlexDimension::Percent(value / 100.0)),
+            "" | "pt" | "px" => Ok(FlexDimension::Points(value)),
+            "em" | "rem" => Ok(FlexDimension::Em(value)),
+            "vw" => Ok(FlexDimension::Vw(value)),
+            "vh" => Ok(FlexDimension::Vh(value)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FlexDimension {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for FlexDimension {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct FlexDimensionVisitor;
+
+        impl<'de> Visitor<'de> for FlexDimensionVisitor {
+            type Value = FlexDimension;
+
+            fn expecting(
+                &self,
+                formatter: &mut std::fmt::Formatter,
+            ) -> std::fmt::Result {
+                formatter.write_str("a length such as auto, 50%, 10pt or 2em")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+                Ok(FlexDimension::Points(v as f32))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(FlexDimension::Points(v as f32))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(FlexDimension::Points(v as f32))
+            }
+        }
+
+        deserializer.deserialize_any(FlexDimensionVisitor)
+    }
+}
+
+/// Defines deserializers that prefix errors with the name of the field.
+/// Fields with a shorthand name the function that parses them.
+#[cfg(feature = "serde")]
+macro_rules! deserialize_named_fields {
+    ($($deserialize:ident => $name:literal,)*) => {$(
+        fn $deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+        where
+            D: Deserializer<'de>,
+            T: Deserialize<'de>,
+        {
+            T::deserialize(deserializer).map_err(field_error($name))
+        }
+    )*};
+    ($($deserialize:ident => $name:literal: $ty:ty = $inner:ident,)*) => {$(
+        fn $deserialize<'de, D>(deserializer: D) -> Result<$ty, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            $inner(deserializer).map_err(field_error($name))
+        }
+    )*};
+}
+
+#[cfg(feature = "serde")]
+fn field_error<E: serde::de::Error>(name: &'static str) -> impl Fn(E) -> E {
+    move |e| E::custom(format!("{name}: {e}"))
+}
+
+#[cfg(feature = "serde")]
+deserialize_named_fields! {
+    deserialize_width => "width",
+    deserialize_height => "height",
+    deserialize_start => "start",
+    deserialize_end => "end",
+    deserialize_top => "top",
+    deserialize_bottom => "bottom",
+    deserialize_flex_basis => "flex-basis",
+    deserialize_min_size => "min-size",
+    deserialize_max_size => "max-size",
+}
 
 #[cfg(feature = "serde")]
-fn deserialize_point<'de, D>(deserializer: D) -> Result<f32, D::Error>
+deserialize_named_fields! {
+    deserialize_position => "position": FlexEdges = deserialize_edges,
+    deserialize_margin => "margin": FlexEdges = deserialize_edges,
+    deserialize_padding => "padding": FlexEdges = deserialize_edges,
+    deserialize_border => "border": FlexEdges = deserialize_edges,
+    deserialize_flex => "flex": Option<FlexShorthand> = deserialize_flex_shorthand,
+    deserialize_gap => "gap": Option<FlexGap> = deserialize_gap_shorthand,
+    deserialize_row_gap => "row-gap": Option<FlexDimension> = deserialize_gap_length,
+    deserialize_column_gap => "column-gap": Option<FlexDimension> = deserialize_gap_length,
+}
+
+/// How the box of a node is painted, whether or not it has an element.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub struct FlexDecoration {
+    /// Paint of the border. Defaults to black once a `border-style` is set.
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    pub border_color: Option<String>,
+    /// Defaults to `solid` once a `border-color` is set
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    pub border_style: Option<FlexBorderStyle>,
+    /// Radius of the outer corners of the border box
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    pub border_radius: Option<FlexDimension>,
+    /// Paint of the border box behind the children: a color, a gradient or
+    /// pattern reference like `url(#fade)`, or an image like `url(bg.png)`
+    /// that covers the box
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    pub background: Option<String>,
+    /// Opacity of the node, its element and its children as a group
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    pub opacity: Option<f32>,
+}
+
+impl FlexDecoration {
+    /// The `href` of an image `background`, as opposed to a paint
+    pub fn background_image(&self) -> Option<&str> {
+        let url = self.background.as_deref()?.trim();
+        let href = url.strip_prefix("url(")?.strip_suffix(')')?;
+        let href = href.trim().trim_matches(|c| c == '"' || c == '\'');
+        (!href.starts_with('#')).then_some(href)
+    }
+
+    /// Style of the border, if it is drawn
+    pub fn border(&self) -> Option<FlexBorderStyle> {
+        let style = match (&self.border_color, self.border_style) {
+            (_, Some(style)) => style,
+            (Some(_), None) => FlexBorderStyle::Solid,
+            (None, None) => return None,
+        };
+        (style != FlexBorderStyle::None).then_some(style)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub enum FlexBorderStyle {
+    None,
+    Solid,
+    /// Only drawn for borders of equal widths, solid otherwise
+    Dashed,
+    /// Only drawn for borders of equal widths, solid otherwise
+    Dotted,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub enum FlexOverflow {
+    #[default]
+    Visible,
+    /// Clips the node and its children to its laid out box. This doesn't
+    /// change the layout.
+    Hidden,
+}
+
+/// CSS transforms of a node. They are applied to the node and its children
+/// after layout, so they never move other nodes.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[cfg_attr(all(feature = "serde"), serde(rename_all = "kebab-case"))]
+pub struct FlexTransform {
+    /// SVG transform list, e.g. `translate(4 0) skewX(10)`, applied after
+    /// `rotate` and `scale`
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    pub transform: Option<String>,
+    /// Clockwise rotation in degrees. Accepts `deg`, `rad`, `grad` and
+    /// `turn` units.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "deserialize_angle"
+        )
+    )]
+    pub rotate: Option<f32>,
+    /// Horizontal and vertical scale, e.g. `2` or `2 0.5`
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "deserialize_scale"
+        )
+    )]
+    pub scale: Option<(f32, f32)>,
+    /// Point the node is transformed around, as fractions of its box like
+    /// `origin`, or CSS keywords and percentages such as `left top`.
+    /// Defaults to the center.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "deserialize_transform_origin"
+        )
+    )]
+    pub transform_origin: Option<(f32, f32)>,
+}
+
+impl FlexTransform {
+    pub fn is_identity(&self) -> bool {
+        self.transform.is_none()
+            && self.rotate.is_none()
+            && self.scale.is_none()
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(f32),
+    String(String),
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_angle<'de, D>(deserializer: D) -> Result<Option<f32>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    // @TODO(jshrake): String allocation
-    // https://users.rust-lang.org/t/serde-deserialize-string-field-in-json-to-a-different-type/12942/13
-    let s = String::deserialize(deserializer)?;
-    if !s.ends_with("pt") {
-        return Err(serde::de::Error::custom("expecting pt"));
+    match Option::<NumberOrString>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(NumberOrString::Number(degrees)) => Ok(Some(degrees)),
+        Some(NumberOrString::String(angle)) => {
+            parse_angle(&angle).map(Some).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+/// Parses a CSS angle into degrees
+#[cfg(feature = "serde")]
+fn parse_angle(s: &str) -> Result<f32, String> {
+    let s = s.trim();
+    let units = [
+        ("deg", 1.0),
+        ("grad", 0.9),
+        ("rad", 180.0 / std::f32::consts::PI),
+        ("turn", 360.0),
+    ];
+    let (value, scale) = units
+        .iter()
+        .find_map(|(unit, scale)| Some((s.strip_suffix(unit)?, *scale)))
+        .unwrap_or((s, 1.0));
+    let value = f32::from_str(value.trim())
+        .map_err(|_| format!("invalid angle `{s}`"))?;
+    Ok(value * scale)
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_scale<'de, D>(
+    deserializer: D,
+) -> Result<Option<(f32, f32)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Scale {
+        Pair((f32, f32)),
+        Other(NumberOrString),
+    }
+    let scale = match Option::<Scale>::deserialize(deserializer)? {
+        None => return Ok(None),
+        Some(Scale::Pair(pair)) => pair,
+        Some(Scale::Other(NumberOrString::Number(scale))) => (scale, scale),
+        Some(Scale::Other(NumberOrString::String(scale))) => {
+            let factors = scale
+                .split_whitespace()
+                .map(f32::from_str)
+                .collect::<Result<Vec<_>, _>>();
+            match factors.as_deref() {
+                Ok([scale]) => (*scale, *scale),
+                Ok([x, y]) => (*x, *y),
+                _ => {
+                    return Err(serde::de::Error::custom(format!(
+                        "invalid scale `{scale}`"
+                    )))
+                }
+            }
+        }
+    };
+    Ok(Some(scale))
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_transform_origin<'de, D>(
+    deserializer: D,
+) -> Result<Option<(f32, f32)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Origin {
+        Pair((f32, f32)),
+        Css(String),
+    }
+    match Option::<Origin>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Origin::Pair(pair)) => Ok(Some(pair)),
+        Some(Origin::Css(origin)) => parse_transform_origin(&origin)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// Parses one or two keywords or percentages, e.g. `left`, `right 25%`
+#[cfg(feature = "serde")]
+fn parse_transform_origin(s: &str) -> Result<(f32, f32), String> {
+    let tokens: Vec<_> = s.split_whitespace().collect();
+    let (x, y) = match tokens[..] {
+        [y @ ("top" | "bottom")] => ("center", y),
+        [x] => (x, "center"),
+        // @NOTE(jshrake): Keywords may be given vertical first
+        [y, x]
+            if matches!(y, "top" | "bottom")
+                || matches!(x, "left" | "right") =>
+        {
+            (x, y)
+        }
+        [x, y] => (x, y),
+        _ => return Err(format!("invalid transform-origin `{s}`")),
+    };
+    let fraction = |token: &str, start, end| match token {
+        "center" => Some(0.5),
+        _ if token == start => Some(0.0),
+        _ if token == end => Some(1.0),
+        _ => token.strip_suffix('%')?.parse::<f32>().ok().map(|p| p / 100.0),
+    };
+    fraction(x, "left", "right")
+        .zip(fraction(y, "top", "bottom"))
+        .ok_or_else(|| format!("invalid transform-origin `{s}`"))
+}
+
+#[cfg(feature = "serde")]
+type FlexEdges = FlexRect<Option<FlexDimension>>;
+
+/// Accepts a map of edges, a single length, or the CSS shorthand
+/// `top [right [bottom [left]]]`, e.g. `4pt 8pt`
+#[cfg(feature = "serde")]
+fn deserialize_edges<'de, D>(deserializer: D) -> Result<FlexEdges, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct EdgesVisitor;
+
+    impl<'de> Visitor<'de> for EdgesVisitor {
+        type Value = FlexEdges;
+
+        fn expecting(
+            &self,
+            formatter: &mut std::fmt::Formatter,
+        ) -> std::fmt::Result {
+            formatter.write_str("lengths such as `4pt 8pt`, or a map of edges")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            parse_edges(v).map_err(E::custom)
+        }
+
+        fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+            let length = Some(FlexDimension::Points(v as f32));
+            Ok(FlexRect {
+                start: length,
+                end: length,
+                top: length,
+                bottom: length,
+            })
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            self.visit_f64(v as f64)
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            self.visit_f64(v as f64)
+        }
+
+        fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            Deserialize::deserialize(MapAccessDeserializer::new(map))
+        }
+    }
+
+    deserializer.deserialize_any(EdgesVisitor)
+}
+
+#[cfg(feature = "serde")]
+fn parse_edges(s: &str) -> Result<FlexEdges, String> {
+    let lengths = crate::grid::split_tokens(s, char::is_whitespace)
+        .into_iter()
+        .map(FlexDimension::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+    let (top, end, bottom, start) = match lengths[..] {
+        [all] => (all, all, all, all),
+        [vertical, horizontal] => (vertical, horizontal, vertical, horizontal),
+        [top, horizontal, bottom] => (top, horizontal, bottom, horizontal),
+        [top, right, bottom, left] => (top, right, bottom, left),
+        _ => return Err(format!("expected 1 to 4 lengths, found `{s}`")),
+    };
+    Ok(FlexRect {
+        start: Some(start),
+        end: Some(end),
+        top: Some(top),
+        bottom: Some(bottom),
+    })
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_flex_shorthand<'de, D>(
+    deserializer: D,
+) -> Result<Option<FlexShorthand>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct FlexVisitor;
+
+    impl<'de> Visitor<'de> for FlexVisitor {
+        type Value = Option<FlexShorthand>;
+
+        fn expecting(
+            &self,
+            formatter: &mut std::fmt::Formatter,
+        ) -> std::fmt::Result {
+            formatter.write_str("a flex shorthand such as `1 1 auto`")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            v.parse().map(Some).map_err(E::custom)
+        }
+
+        fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+            Ok(Some(FlexShorthand::grow(v as f32)))
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            self.visit_f64(v as f64)
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            self.visit_f64(v as f64)
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+    }
+
+    deserializer.deserialize_any(FlexVisitor)
+}
+
+/// The CSS `flex` shorthand.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FlexShorthand {
+    pub grow: f32,
+    pub shrink: f32,
+    pub basis: FlexDimension,
+}
+
+impl FlexShorthand {
+    /// `flex: <grow>`, which grows from a basis of zero
+    pub fn grow(grow: f32) -> Self {
+        FlexShorthand { grow, shrink: 1.0, basis: FlexDimension::Percent(0.0) }
+    }
+}
+
+impl std::fmt::Display for FlexShorthand {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.grow, self.shrink, self.basis)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromStr for FlexShorthand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let auto = FlexDimension::Auto;
+        let (grow, shrink) = match s.trim() {
+            "none" => (0.0, 0.0),
+            "auto" => (1.0, 1.0),
+            "initial" => (0.0, 1.0),
+            _ => return parse_flex(s),
+        };
+        Ok(FlexShorthand { grow, shrink, basis: auto })
+    }
+}
+
+/// Parses `<grow> [<shrink>] [<basis>]`, in any order
+#[cfg(feature = "serde")]
+fn parse_flex(s: &str) -> Result<FlexShorthand, String> {
+    let mut factors = vec![];
+    let mut basis = None;
+    for token in crate::grid::split_tokens(s, char::is_whitespace) {
+        match f32::from_str(token) {
+            Ok(factor) if factors.len() < 2 => factors.push(factor),
+            _ if basis.is_none() => basis = Some(token.parse()?),
+            _ => return Err(format!("invalid flex `{s}`")),
+        }
+    }
+    // @NOTE(jshrake): Omitted factors are 1, and an omitted basis is 0
+    Ok(FlexShorthand {
+        grow: factors.first().copied().unwrap_or(1.0),
+        shrink: factors.get(1).copied().unwrap_or(1.0),
+        basis: basis.unwrap_or(FlexDimension::Percent(0.0)),
+    })
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FlexShorthand {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_gap_shorthand<'de, D>(
+    deserializer: D,
+) -> Result<Option<FlexGap>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct GapVisitor;
+
+    impl<'de> Visitor<'de> for GapVisitor {
+        type Value = Option<FlexGap>;
+
+        fn expecting(
+            &self,
+            formatter: &mut std::fmt::Formatter,
+        ) -> std::fmt::Result {
+            formatter.write_str("one or two lengths such as `10 20`")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            v.parse().map(Some).map_err(E::custom)
+        }
+
+        fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+            let length = FlexDimension::Points(v as f32);
+            Ok(Some(FlexGap { row: length, column: length }))
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            self.visit_f64(v as f64)
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            self.visit_f64(v as f64)
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+    }
+
+    deserializer.deserialize_any(GapVisitor)
+}
+
+/// A `row-gap` or `column-gap`, which can't be `auto`
+#[cfg(feature = "serde")]
+fn deserialize_gap_length<'de, D>(
+    deserializer: D,
+) -> Result<Option<FlexDimension>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<FlexDimension>::deserialize(deserializer)? {
+        Some(FlexDimension::Auto) => {
+            Err(serde::de::Error::custom(AUTO_GAP_ERROR))
+        }
+        length => Ok(length),
+    }
+}
+
+#[cfg(feature = "serde")]
+const AUTO_GAP_ERROR: &str = "invalid gap `auto`, expected a length";
+
+/// The CSS `gap` shorthand, `<row-gap> [<column-gap>]`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FlexGap {
+    pub row: FlexDimension,
+    pub column: FlexDimension,
+}
+
+impl std::fmt::Display for FlexGap {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {}", self.row, self.column)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromStr for FlexGap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lengths = crate::grid::split_tokens(s, char::is_whitespace)
+            .into_iter()
+            .map(FlexDimension::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        if lengths.contains(&FlexDimension::Auto) {
+            return Err(AUTO_GAP_ERROR.to_string());
+        }
+        match lengths[..] {
+            [gap] => Ok(FlexGap { row: gap, column: gap }),
+            [row, column] => Ok(FlexGap { row, column }),
+            _ => Err(format!("expected 1 or 2 lengths, found `{s}`")),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FlexGap {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
     }
-    let cap = RE_F32
-        .captures(&s)
-        .and_then(|c| c.get(1))
-        .ok_or_else(|| serde::de::Error::custom("invalid point"))?;
-    f32::from_str(cap.as_str()).map_err(serde::de::Error::custom)
 }
 
 /*
@@ -426,8 +1967,235 @@ pub struct FlexPoint {
 
 #[derive(Debug, Clone)]
 pub(crate) struct FlexTaffyNode {
-    pub taffy_node: TaffyNode,
+    pub taffy_node: Node,
+    /// Layout of a node with `calc()` dimensions, restyled once its parent
+    /// is laid out
+    pub calc_layout: Option<FlexLayout>,
+    pub decoration: FlexDecoration,
     pub element: Option<FlexElement>,
+    pub text: Option<FlexText>,
+    pub id: Option<String>,
+    pub transform: FlexTransform,
+    pub z_index: i32,
+    pub overflow: FlexOverflow,
     pub root: Option<FlexRootData>,
+    pub is_label: bool,
     pub nodes: Vec<FlexTaffyNode>,
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    fn length(s: &str) -> FlexDimension {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_bare_numbers_as_points() {
+        assert_eq!(length("12"), FlexDimension::Points(12.0));
+        assert_eq!(length("1.5"), FlexDimension::Points(1.5));
+        assert_eq!(length(".5"), FlexDimension::Points(0.5));
+        assert_eq!(length("1e2"), FlexDimension::Points(100.0));
+    }
+
+    #[test]
+    fn parses_negative_lengths() {
+        assert_eq!(length("-5pt"), FlexDimension::Points(-5.0));
+        assert_eq!(length("-.5em"), FlexDimension::Em(-0.5));
+        assert_eq!(length("-10%"), FlexDimension::Percent(-0.1));
+    }
+
+    #[test]
+    fn parses_units() {
+        assert_eq!(length("5%"), FlexDimension::Percent(0.05));
+        assert_eq!(length("4px"), FlexDimension::Points(4.0));
+        assert_eq!(length("2em"), FlexDimension::Em(2.0));
+        assert_eq!(length("2rem"), FlexDimension::Em(2.0));
+        assert_eq!(length("50vw"), FlexDimension::Vw(50.0));
+        assert_eq!(length("25vh"), FlexDimension::Vh(25.0));
+        assert_eq!(length(" auto "), FlexDimension::Auto);
+    }
+
+    #[test]
+    fn rejects_unknown_units() {
+        for s in ["5qq", "px", "1.2.3pt", "5 %x", ""] {
+            let error = s.parse::<FlexDimension>().unwrap_err();
+            assert!(error.starts_with("invalid length"), "{s}: {error}");
+        }
+    }
+
+    #[test]
+    fn deserializes_numbers_and_strings() {
+        let size: FlexSize<FlexDimension> =
+            serde_yaml::from_str("{width: 10, height: 2em}").unwrap();
+        assert_eq!(size.width, FlexDimension::Points(10.0));
+        assert_eq!(size.height, FlexDimension::Em(2.0));
+    }
+
+    fn layout(yaml: &str) -> FlexLayout {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn parses_edge_shorthands() {
+        let (four, eight) = (
+            Some(FlexDimension::Points(4.0)),
+            Some(FlexDimension::Points(8.0)),
+        );
+        let padding = layout("padding: 4pt 8pt").padding;
+        let expected =
+            FlexRect { start: eight, end: eight, top: four, bottom: four };
+        assert_eq!(padding, expected);
+        let padding = layout("padding: 1 2 3").padding;
+        assert_eq!(padding.top, Some(FlexDimension::Points(1.0)));
+        assert_eq!(padding.start, Some(FlexDimension::Points(2.0)));
+        assert_eq!(padding.end, Some(FlexDimension::Points(2.0)));
+        assert_eq!(padding.bottom, Some(FlexDimension::Points(3.0)));
+        let margin = layout("margin: 1 2 3 auto").margin;
+        assert_eq!(margin.end, Some(FlexDimension::Points(2.0)));
+        assert_eq!(margin.start, Some(FlexDimension::Auto));
+        let border = layout("border: 4").border;
+        assert_eq!(
+            border,
+            FlexRect { start: four, end: four, top: four, bottom: four }
+        );
+        assert!(
+            serde_yaml::from_str::<FlexLayout>("padding: 1 2 3 4 5").is_err()
+        );
+    }
+
+    #[test]
+    fn accepts_inset_and_left_right_aliases() {
+        let zero = Some(FlexDimension::Points(0.0));
+        let position = layout("inset: 0").position;
+        assert_eq!(
+            position,
+            FlexRect { start: zero, end: zero, top: zero, bottom: zero }
+        );
+        let margin = layout("margin: {left: 4, right: 10%}").margin;
+        assert_eq!(margin.start, Some(FlexDimension::Points(4.0)));
+        assert_eq!(margin.end, Some(FlexDimension::Percent(0.1)));
+        assert_eq!(margin.top, None);
+    }
+
+    #[test]
+    fn parses_flex_shorthands() {
+        let flex = |s: &str| s.parse::<FlexShorthand>().unwrap();
+        let shorthand =
+            |grow, shrink, basis| FlexShorthand { grow, shrink, basis };
+        assert_eq!(flex("1 1 auto"), shorthand(1.0, 1.0, FlexDimension::Auto));
+        assert_eq!(flex("2"), shorthand(2.0, 1.0, FlexDimension::Percent(0.0)));
+        assert_eq!(
+            flex("2 0"),
+            shorthand(2.0, 0.0, FlexDimension::Percent(0.0))
+        );
+        assert_eq!(
+            flex("10pt"),
+            shorthand(1.0, 1.0, FlexDimension::Points(10.0))
+        );
+        assert_eq!(flex("none"), shorthand(0.0, 0.0, FlexDimension::Auto));
+        assert_eq!(flex("auto"), shorthand(1.0, 1.0, FlexDimension::Auto));
+        assert_eq!(flex("initial"), shorthand(0.0, 1.0, FlexDimension::Auto));
+        assert_eq!(
+            flex("1 2 3"),
+            shorthand(1.0, 2.0, FlexDimension::Points(3.0))
+        );
+        assert!("1 2 3 4".parse::<FlexShorthand>().is_err());
+        assert!("1 auto 10pt".parse::<FlexShorthand>().is_err());
+        assert_eq!(layout("flex: 3").flex, Some(FlexShorthand::grow(3.0)));
+    }
+
+    #[test]
+    fn flex_overrides_the_longhands() {
+        let units = FlexUnits::default();
+        for yaml in ["{flex: 2, flex-grow: 5}", "{flex-grow: 5, flex: 2}"] {
+            let style = layout(yaml).to_style(&units);
+            assert_eq!(style.flex_grow, 2.0, "{yaml}");
+            assert_eq!(style.flex_basis, Dimension::Percent(0.0), "{yaml}");
+        }
+        let style = layout("{flex-grow: 5, flex-shrink: 0}").to_style(&units);
+        assert_eq!((style.flex_grow, style.flex_shrink), (5.0, 0.0));
+    }
+
+    #[test]
+    fn deserializes_box_and_svg_points() {
+        let points = |yaml: &str| {
+            let node = serde_yaml::from_str::<FlexNode>(yaml).unwrap();
+            node.element.and_then(|element| element.points)
+        };
+        assert_eq!(
+            points("{element: polygon, points: [[0, 0], [1, 0.5]]}"),
+            Some(FlexPoints::Box(vec![(0.0, 0.0), (1.0, 0.5)]))
+        );
+        assert_eq!(
+            points("{element: polygon, points: '0,0 10,0 5,10'}"),
+            Some(FlexPoints::Svg("0,0 10,0 5,10".to_string()))
+        );
+    }
+
+    #[test]
+    fn parses_gap_shorthands() {
+        let gap = |row, column| Some(FlexGap { row, column });
+        let (ten, twenty) =
+            (FlexDimension::Points(10.0), FlexDimension::Points(20.0));
+        assert_eq!(layout("gap: 10").gap, gap(ten, ten));
+        assert_eq!(layout("gap: 10 20").gap, gap(ten, twenty));
+        assert_eq!(
+            layout("gap: 10 5%").gap,
+            gap(ten, FlexDimension::Percent(0.05))
+        );
+        let style = layout("gap: 10 20").to_style(&FlexUnits::default());
+        assert_eq!(style.gap.height, LengthPercentage::Points(10.0));
+        assert_eq!(style.gap.width, LengthPercentage::Points(20.0));
+        let style = layout("{gap: 10 20, column-gap: 4}")
+            .to_style(&FlexUnits::default());
+        assert_eq!(style.gap.width, LengthPercentage::Points(4.0));
+        for yaml in [
+            "gap: auto",
+            "gap: 10 auto",
+            "gap: 1 2 3",
+            "row-gap: auto",
+            "column-gap: auto",
+        ] {
+            let error = serde_yaml::from_str::<FlexLayout>(yaml).unwrap_err();
+            let field = yaml.split(' ').next().unwrap();
+            assert!(error.to_string().starts_with(field), "{yaml}: {error}");
+        }
+    }
+
+    #[test]
+    fn rejects_negative_edge_spacing() {
+        let root = |yaml: &str| serde_yaml::from_str::<FlexRootData>(yaml);
+        assert_eq!(root("edge-spacing: 0").unwrap().edge_spacing, 0.0);
+        let error = root("edge-spacing: -4").unwrap_err().to_string();
+        assert!(error.starts_with("edge-spacing: "), "{error}");
+    }
+
+    #[test]
+    fn defaults_missing_canvas_axes() {
+        let root = |yaml: &str| {
+            let node = serde_yaml::from_str::<FlexNode>(yaml).unwrap();
+            node.root.unwrap().canvas
+        };
+        let canvas = root("canvas: {width: 200}");
+        assert_eq!(canvas, FlexSize { width: 200.0, height: 100.0 });
+        let canvas = root("canvas: {height: 50}");
+        assert_eq!(canvas, FlexSize { width: 100.0, height: 50.0 });
+    }
+
+    #[test]
+    fn errors_name_the_field() {
+        for (yaml, field) in [
+            ("width: 10qq", "width: "),
+            ("height: big", "height: "),
+            ("flex-basis: 1x", "flex-basis: "),
+            ("margin: 1pt 2qq", "margin: "),
+        ] {
+            let error = serde_yaml::from_str::<FlexNode>(yaml).unwrap_err();
+            let error = error.to_string();
+            assert!(error.contains(field), "{yaml}: {error}");
+            assert!(error.contains("invalid length"), "{yaml}: {error}");
+        }
+    }
+}